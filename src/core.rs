@@ -6,11 +6,12 @@ pub mod data;
 pub mod history;
 pub mod jobtable;
 pub mod options;
+pub mod trap;
 
 use self::data::Data;
 use self::options::Options;
 use std::collections::HashMap;
-use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
 use std::{io, env, path, process};
 use nix::{fcntl, unistd};
 use nix::sys::{resource, signal, wait};
@@ -26,6 +27,7 @@ use crate::core::jobtable::JobEntry;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::Relaxed;
+use signal_hook::SigId;
 
 pub struct ShellCore {
     pub data: Data,
@@ -41,6 +43,7 @@ pub struct ShellCore {
     pub eval_level: i32,
     pub loop_level: i32,
     pub break_counter: i32,
+    pub continue_counter: i32,
     pub return_flag: bool,
     pub tty_fd: Option<OwnedFd>,
     pub job_table: Vec<JobEntry>,
@@ -52,8 +55,16 @@ pub struct ShellCore {
     pub sys_time: TimeVal, 
     pub options: Options,
     pub shopts: Options,
-    pub suspend_e_option: bool,
+    pub errexit_ignored: bool, // in a condition, a non-final member of && or ||, or a ! pipeline
+    pub errexit_exempted: bool, // $? comes from a command in the above contexts
+    pub exit_warned: bool, // for jobs remaining at exit
     pub script_name: String,
+    pub traps: HashMap<String, String>,
+    pub trap_flags: HashMap<String, (Arc<AtomicBool>, SigId)>,
+    pub trap_running: bool,
+    pub start_time: i64,
+    pub proc_substs: Vec<(RawFd, Option<Pid>)>,
+    proc_subst_pids: Vec<Pid>,
 }
 
 fn ignore_signal(sig: Signal) {
@@ -82,6 +93,7 @@ impl ShellCore {
             eval_level: 0,
             loop_level: 0,
             break_counter: 0,
+            continue_counter: 0,
             return_flag: false,
             tty_fd: None,
             job_table: vec![],
@@ -93,23 +105,33 @@ impl ShellCore {
             sys_time: TimeVal::new(0, 0),
             options: Options::new_as_basic_opts(),
             shopts: Options::new_as_shopts(),
-            suspend_e_option: false,
+            errexit_ignored: false,
+            errexit_exempted: false,
+            exit_warned: false,
             script_name: "-".to_string(),
+            traps: HashMap::new(),
+            trap_flags: HashMap::new(),
+            trap_running: false,
+            start_time: time::clock_gettime(ClockId::CLOCK_REALTIME)
+                        .map(|t| t.tv_sec()).unwrap_or(0),
+            proc_substs: vec![],
+            proc_subst_pids: vec![],
         };
 
         core.init_current_directory();
         core.set_initial_parameters();
         core.set_builtins();
-        ignore_signal(Signal::SIGPIPE);
-        ignore_signal(Signal::SIGTSTP);
+        core.set_ignored_signals(true);
 
         core.data.set_param("PS4", "+ ");
+        core.data.set_param("IFS", " \t\n");
 
         if unistd::isatty(0) == Ok(true) {
             const V: &'static str = env!("CARGO_PKG_VERSION");
             eprintln!("Rusty Bash (a.k.a. Sushi shell), version {}", V);
 
-            core.data.flags += "i";
+            core.data.flags += "im";
+            core.shopts.set("expand_aliases", true);
             core.read_stdin = false;
             core.data.set_param("PS1", "🍣 ");
             core.data.set_param("PS2", "> ");
@@ -225,11 +247,20 @@ impl ShellCore {
     fn check_e_option(&mut self) {
         if self.data.get_param("?") != "0" 
         && self.data.flags.contains("e") 
-        && ! self.suspend_e_option {
+        && ! self.errexit_ignored
+        && ! self.errexit_exempted {
             self.exit();
         }
     }
 
+    /* runs a condition part in which a failure never triggers errexit */
+    pub fn exec_ignoring_errexit<F: FnOnce(&mut ShellCore)>(&mut self, f: F) {
+        let backup = self.errexit_ignored;
+        self.errexit_ignored = true;
+        f(self);
+        self.errexit_ignored = backup;
+    }
+
     pub fn wait_pipeline(&mut self, pids: Vec<Option<Pid>>,
                          exclamation: bool, time: bool) -> Vec<WaitStatus> {
         if pids.len() == 1 && pids[0] == None {
@@ -238,16 +269,21 @@ impl ShellCore {
             }
             if exclamation {
                 self.flip_exit_status();
+                self.errexit_exempted = true;
             }
+            self.run_err_trap();
             self.check_e_option();
             return vec![];
         }
 
         let mut pipestatus = vec![];
         let mut ans = vec![];
+        let lastpipe_status = self.data.get_param("?");
         for pid in &pids {
-            let ws = self.wait_process(pid.expect("SUSHI INTERNAL ERROR (no pid)"));
-            ans.push(ws);
+            match pid {
+                Some(p) => ans.push(self.wait_process(*p)),
+                None    => self.data.set_param("?", &lastpipe_status),
+            }
 
             pipestatus.push(self.data.get_param("?"));
         }
@@ -269,12 +305,53 @@ impl ShellCore {
         if exclamation {
             self.flip_exit_status();
         }
-
+        self.errexit_exempted = exclamation;
+        self.run_err_trap();
         self.check_e_option();
 
         ans
     }
 
+    /* closes the fds of process substitutions after the command using them.
+       the children are reaped without blocking so as not to hang on a reader
+       kept alive by exec */
+    pub fn close_proc_substs(&mut self, from: usize) {
+        if from >= self.proc_substs.len() && self.proc_subst_pids.is_empty() {
+            return;
+        }
+
+        for (fd, pid) in self.proc_substs.split_off(from) {
+            let _ = unistd::close(fd);
+            self.proc_subst_pids.extend(pid);
+        }
+
+        self.proc_subst_pids.retain(|pid| {
+            matches!(wait::waitpid(*pid, Some(WaitPidFlag::WNOHANG)),
+                     Ok(WaitStatus::StillAlive))
+        });
+    }
+
+    pub fn check_loop_exit(&mut self) -> bool {
+        if self.break_counter > 0 {
+            self.break_counter -= 1;
+            return true;
+        }
+
+        if self.continue_counter > 0 {
+            self.continue_counter -= 1;
+            return self.continue_counter > 0; //true when an outer loop is the target
+        }
+        false
+    }
+
+    pub fn leave_loop(&mut self) {
+        self.loop_level -= 1;
+        if self.loop_level == 0 {
+            self.break_counter = 0;
+            self.continue_counter = 0;
+        }
+    }
+
     pub fn run_builtin(&mut self, args: &mut Vec<String>, special_args: &mut Vec<String>) -> bool {
         if args.len() == 0 {
             error_message::internal(" (no arg for builtins)");
@@ -291,7 +368,21 @@ impl ShellCore {
         false
     }
 
+    /* options of set -o: the single-letter ones are kept in $- */
+    pub fn query_option(&self, opt: &str) -> bool {
+        match Options::flag_of(opt) {
+            Some(flag) => self.data.flags.contains(flag),
+            None       => self.options.query(opt),
+        }
+    }
+
     pub fn exit(&mut self) -> ! {
+        if self.data.flags.contains('i') && ! self.is_subshell
+        && self.shopts.query("huponexit") {
+            self.jobtable_send_hup();
+        }
+        self.run_trap("EXIT");
+        self.traps.remove("EXIT");
         self.write_history_to_file();
 
         let es_str = self.data.get_param("?");
@@ -317,21 +408,50 @@ impl ShellCore {
     }
 
     pub fn set_pgid(&self, pid: Pid, pgid: Pid) {
+        if self.tty_fd.is_some() && ! self.data.flags.contains('m') {
+            return; //set +m: commands stay in the process group of the shell
+        }
+
         let _ = unistd::setpgid(pid, pgid);
         if pid.as_raw() == 0 && pgid.as_raw() == 0 { //以下3行追加
             self.set_foreground();
         }
     }
 
+    /* ignored signals are inherited through exec, so they are
+       restored before exec and ignored again when it fails */
+    pub fn set_ignored_signals(&self, ignore: bool) {
+        for sig in [Signal::SIGPIPE, Signal::SIGTSTP] {
+            match ignore {
+                true  => ignore_signal(sig),
+                false => restore_signal(sig),
+            }
+        }
+    }
+
+    /* the environment of an exec'd command is made only of exported variables */
+    pub fn set_environment_variables(&mut self) {
+        for (k, _) in env::vars() {
+            env::remove_var(k);
+        }
+        for (k, v) in self.data.get_exported() {
+            env::set_var(k, v);
+        }
+    }
+
     pub fn initialize_as_subshell(&mut self, pid: Pid, pgid: Pid){
         restore_signal(Signal::SIGINT);
         restore_signal(Signal::SIGTSTP);
         restore_signal(Signal::SIGPIPE);
 
         self.is_subshell = true;
+        self.loop_level = 0;
+        self.reset_traps_in_subshell();
         self.set_pgid(pid, pgid);
         self.set_subshell_parameters();
         self.job_table.clear();
+        self.proc_substs.clear();
+        self.proc_subst_pids.clear();
     }
 
     pub fn init_current_directory(&mut self) {
@@ -360,6 +480,13 @@ impl ShellCore {
         res
     }
 
+    pub fn get_ifs(&mut self) -> String {
+        match self.data.get_value("IFS") {
+            Some(_) => self.data.get_param("IFS"),
+            None    => " \t\n".to_string(),
+        }
+    }
+
     pub fn get_ps4(&mut self) -> String {
         let ps4 = self.data.get_param("PS4").trim_end().to_string();
         let mut multi_ps4 = ps4.to_string();
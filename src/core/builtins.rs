@@ -3,15 +3,23 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod cd;
+mod declare;
+mod echo;
+mod exec;
+mod hash;
 pub mod completion;
 mod history;
 mod job_commands;
 mod local;
+mod mapfile;
 pub mod option_commands;
+mod printf;
 mod pwd;
-mod read;
+pub mod read;
 mod source;
 mod return_break;
+mod test;
+mod trap;
 mod unset;
 mod utils;
 
@@ -20,28 +28,45 @@ use crate::{Feeder, Script, ShellCore};
 impl ShellCore {
     pub fn set_builtins(&mut self) {
         self.builtins.insert(":".to_string(), true_);
+        self.builtins.insert("[".to_string(), test::test);
         self.builtins.insert("alias".to_string(), alias);
         self.builtins.insert("bg".to_string(), job_commands::bg);
         self.builtins.insert("break".to_string(), return_break::break_);
         self.builtins.insert("cd".to_string(), cd::cd);
         self.builtins.insert("compgen".to_string(), completion::compgen);
         self.builtins.insert("complete".to_string(), completion::complete);
+        self.builtins.insert("continue".to_string(), return_break::continue_);
+        self.builtins.insert("declare".to_string(), declare::declare);
+        self.builtins.insert("disown".to_string(), job_commands::disown);
+        self.builtins.insert("echo".to_string(), echo::echo);
         self.builtins.insert("eval".to_string(), eval);
+        self.builtins.insert("exec".to_string(), exec::exec);
         self.builtins.insert("exit".to_string(), exit);
+        self.builtins.insert("export".to_string(), declare::export);
         self.builtins.insert("false".to_string(), false_);
         self.builtins.insert("fg".to_string(), job_commands::fg);
+        self.builtins.insert("hash".to_string(), hash::hash);
         self.builtins.insert("history".to_string(), history::history);
         self.builtins.insert("jobs".to_string(), job_commands::jobs);
+        self.builtins.insert("kill".to_string(), job_commands::kill);
         self.builtins.insert("local".to_string(), local::local);
+        self.builtins.insert("mapfile".to_string(), mapfile::mapfile);
+        self.builtins.insert("printf".to_string(), printf::printf);
         self.builtins.insert("pwd".to_string(), pwd::pwd);
         self.builtins.insert("read".to_string(), read::read);
+        self.builtins.insert("readarray".to_string(), mapfile::mapfile);
+        self.builtins.insert("readonly".to_string(), declare::readonly);
         self.builtins.insert("return".to_string(), return_break::return_);
         self.builtins.insert("set".to_string(), option_commands::set);
         self.builtins.insert("shopt".to_string(), option_commands::shopt);
         self.builtins.insert("unset".to_string(), unset::unset);
         self.builtins.insert("source".to_string(), source::source);
+        self.builtins.insert("suspend".to_string(), job_commands::suspend);
         self.builtins.insert(".".to_string(), source::source);
+        self.builtins.insert("test".to_string(), test::test);
+        self.builtins.insert("trap".to_string(), trap::trap);
         self.builtins.insert("true".to_string(), true_);
+        self.builtins.insert("typeset".to_string(), declare::declare);
         self.builtins.insert("wait".to_string(), job_commands::wait);
     }
 }
@@ -80,6 +105,9 @@ pub fn eval(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 
 pub fn exit(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     eprintln!("exit");
+    if core.jobtable_defer_exit() {
+        return 1;
+    }
     if args.len() > 1 {
         core.data.set_layer_param("?", &args[1], 0);
     }
@@ -3,6 +3,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
+use crate::utils::directory;
 use super::utils;
 
 pub fn cd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
@@ -55,8 +56,23 @@ fn change_directory(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if core.set_current_directory(&path).is_ok() {
         core.data.set_layer_param("PWD", &path.display().to_string(), 0);
         0
+    }else if let Some(corrected) = correct_spelling(core, &args[1]) {
+        println!("{}", &corrected);
+        args[1] = corrected;
+        change_directory(core, args)
     }else{
         eprintln!("sush: cd: {:?}: No such file or directory", &path);
         1
     }
 }
+
+fn correct_spelling(core: &mut ShellCore, dir: &str) -> Option<String> {
+    if ! core.data.flags.contains('i') || ! core.shopts.query("cdspell") {
+        return None;
+    }
+
+    match directory::correct_spelling(dir) {
+        Some(d) if d != dir => Some(d),
+        _ => None,
+    }
+}
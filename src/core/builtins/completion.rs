@@ -31,14 +31,19 @@ pub fn compgen_f(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     };
 
     split.push("".to_string());
-    let dir = split.join("/");
+    let mut dir = split.join("/");
+    if core.shopts.query("dirspell") && dir.len() > 1 && ! file_check::is_dir(&dir) {
+        if let Some(d) = directory::correct_spelling(&dir) {
+            dir = d + "/";
+        }
+    }
 
     if key == "" {
         let files = directory::files(&dir);
         return files.iter().map(|f| dir.clone() + &f).collect();
     }
 
-    let mut ans = directory::glob(&dir, &(key + "*"), core.shopts.query("extglob"));
+    let mut ans = directory::glob(&dir, &(key + "*"), core.shopts.query("extglob"), false, false);
     ans.iter_mut().for_each(|a| { a.pop(); } );
     ans.sort();
     ans
@@ -0,0 +1,299 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::core::data::Value;
+use crate::elements::substitution::Substitution;
+use std::collections::HashMap;
+
+const ATTRIBUTES: &str = "aAinrxlu";
+
+fn quote(s: &str) -> String {
+    let mut ans = "\"".to_string();
+    for ch in s.chars() {
+        if "\"\\$`".contains(ch) {
+            ans.push('\\');
+        }
+        ans.push(ch);
+    }
+    ans + "\""
+}
+
+fn attribute_string(name: &str, core: &mut ShellCore) -> String {
+    let attrs = core.data.get_attributes(name);
+    let mut ans: String = ATTRIBUTES.chars().filter(|c| attrs.contains(*c)).collect();
+
+    let kind = match core.data.get_raw_value(name) {
+        Some(Value::EvaluatedArray(_)) => 'a',
+        Some(Value::AssocArray(_)) => 'A',
+        _ => ' ',
+    };
+    if kind != ' ' && ! ans.contains(kind) {
+        ans.insert(0, kind);
+    }
+
+    match ans.len() {
+        0 => "--".to_string(),
+        _ => "-".to_string() + &ans,
+    }
+}
+
+fn print_definition(name: &str, core: &mut ShellCore) -> bool {
+    let value = core.data.get_raw_value(name);
+    let attrs = core.data.get_attributes(name);
+    if value.is_none() && attrs.is_empty() {
+        eprintln!("sush: declare: {}: not found", name);
+        return false;
+    }
+
+    let head = format!("declare {} {}", attribute_string(name, core), name);
+    match value {
+        Some(Value::EvaluatedSingle(s)) => println!("{}={}", head, quote(&s)),
+        Some(Value::EvaluatedArray(a)) => {
+            let elems: Vec<String> = a.iter()
+                                      .map(|(i, v)| format!("[{}]={}", i, quote(v)))
+                                      .collect();
+            println!("{}=({})", head, elems.join(" "));
+        },
+        Some(Value::AssocArray(_)) => {
+            let mut elems = String::new();
+            for k in core.data.get_array_keys(name) {
+                let v = core.data.get_array(name, &k);
+                elems += &format!("[{}]={} ", k, quote(&v));
+            }
+            println!("{}=({})", head, elems);
+        },
+        _ => println!("{}", head),
+    }
+    true
+}
+
+fn print_all(core: &mut ShellCore) -> i32 {
+    for name in core.data.get_keys() {
+        if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && ! name.starts_with(|c: char| c.is_ascii_digit()) {
+            print_definition(&name, core);
+        }
+    }
+    0
+}
+
+fn set_attributes(name: &str, options: &HashMap<char, bool>, core: &mut ShellCore, layer: usize) {
+    for (opt, on) in options {
+        match (opt, on) {
+            ('a', true) => match core.data.get_raw_value(name) {
+                Some(Value::EvaluatedArray(_)) => {},
+                Some(Value::EvaluatedSingle(s)) => core.data.set_layer_array(name, &vec![s], layer),
+                _ => core.data.set_layer_array(name, &vec![], layer),
+            },
+            ('A', true) => match core.data.get_raw_value(name) {
+                Some(Value::AssocArray(_)) => {},
+                _ => core.data.set_layer_assoc(name, &HashMap::new(), layer),
+            },
+            ('l', true) => core.data.remove_layer_attribute(name, 'u', layer),
+            ('u', true) => core.data.remove_layer_attribute(name, 'l', layer),
+            _ => {},
+        }
+
+        if "inrxlu".contains(*opt) {
+            match on {
+                true  => core.data.set_layer_attribute(name, *opt, layer),
+                false => if *opt != 'r' {
+                    core.data.remove_layer_attribute(name, *opt, layer)
+                },
+            }
+        }
+    }
+}
+
+pub fn declare_one(com: &str, arg: &str, options: &HashMap<char, bool>,
+                   core: &mut ShellCore, layer: usize) -> bool {
+    let mut sub = match Substitution::parse(&mut Feeder::new(arg), core) {
+        Some(s) => s,
+        None => {
+            let valid = ! arg.starts_with(|c: char| c.is_ascii_digit())
+                        && arg.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if ! valid || arg.is_empty() {
+                eprintln!("sush: {}: `{}': not a valid identifier", com, arg);
+                return false;
+            }
+            set_attributes(arg, options, core, layer);
+            core.data.declare_layer_param(arg, layer);
+            return true;
+        },
+    };
+
+    if core.data.get_attributes(&sub.key).contains('r') {
+        eprintln!("sush: {}: {}: readonly variable", com, &sub.key);
+        return false;
+    }
+
+    let readonly = options.get(&'r') == Some(&true);
+    let mut attrs = options.clone();
+    attrs.remove(&'r');
+    set_attributes(&sub.key, &attrs, core, layer);
+
+    let index = sub.eval_index(core);
+    let value = sub.eval(core);
+    match (index, value) {
+        (_, Value::None) => return false,
+        (Some(pos), Value::EvaluatedSingle(v)) if options.get(&'n') != Some(&true) => {
+            core.data.set_layer_array_elem(&sub.key, &pos, &v, layer);
+        },
+        (_, Value::EvaluatedSingle(v)) if options.get(&'n') == Some(&true) => {
+            core.data.set_layer_raw_param(&sub.key, &v, layer);
+        },
+        (_, Value::EvaluatedSingle(v)) if core.data.is_assoc(&sub.key) => {
+            core.data.set_layer_array_elem(&sub.key, "0", &v, layer);
+        },
+        (_, v) => core.data.set_layer_value(&sub.key, &v, layer),
+    }
+
+    if readonly {
+        core.data.set_layer_attribute(&sub.key, 'r', layer);
+    }
+    true
+}
+
+fn print_with_attribute(attr: char, core: &mut ShellCore) -> i32 {
+    for name in core.data.get_keys() {
+        if core.data.get_attributes(&name).contains(attr) {
+            print_definition(&name, core);
+        }
+    }
+    0
+}
+
+fn set_with_attribute(com: &str, args: &[String], options: &HashMap<char, bool>, core: &mut ShellCore) -> i32 {
+    let mut exit_status = 0;
+    for a in args {
+        let name = a.split(['=', '[']).next().unwrap_or("");
+        let layer = core.data.get_var_layer(name);
+        if ! declare_one(com, a, options, core, layer) {
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
+
+pub fn export(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut options = HashMap::new();
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") {
+        match args[pos].as_str() {
+            "-n" => {options.insert('x', false);},
+            "-p" => {},
+            "--" => {
+                pos += 1;
+                break;
+            },
+            opt => {
+                eprintln!("sush: export: {}: invalid option", opt);
+                eprintln!("export: usage: export [-n] [name[=value] ...] or export -p");
+                return 2;
+            },
+        }
+        pos += 1;
+    }
+
+    if pos == args.len() {
+        return print_with_attribute('x', core);
+    }
+
+    if options.is_empty() {
+        options.insert('x', true);
+    }
+    set_with_attribute("export", &args[pos..], &options, core)
+}
+
+pub fn readonly(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut options = HashMap::new();
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") {
+        match args[pos].as_str() {
+            "-a" => {options.insert('a', true);},
+            "-A" => {options.insert('A', true);},
+            "-p" => {},
+            "--" => {
+                pos += 1;
+                break;
+            },
+            opt => {
+                eprintln!("sush: readonly: {}: invalid option", opt);
+                eprintln!("readonly: usage: readonly [-aA] [name[=value] ...] or readonly -p");
+                return 2;
+            },
+        }
+        pos += 1;
+    }
+
+    if pos == args.len() {
+        return print_with_attribute('r', core);
+    }
+
+    options.insert('r', true);
+    set_with_attribute("readonly", &args[pos..], &options, core)
+}
+
+/* options of declare, typeset and local */
+pub fn parse_options(args: &[String], pos: &mut usize) -> Option<HashMap<char, bool>> {
+    let mut options = HashMap::new();
+    while *pos < args.len() {
+        let a = &args[*pos];
+        if a == "--" {
+            *pos += 1;
+            break;
+        }
+        if a.len() < 2 || ! (a.starts_with("-") || a.starts_with("+")) {
+            break;
+        }
+
+        for ch in a[1..].chars() {
+            if ! "aAinrxlugp".contains(ch) {
+                eprintln!("sush: {}: -{}: invalid option", &args[0], ch);
+                eprintln!("{}: usage: {} [-aAgilnrux] [-p] [name[=value] ...]", &args[0], &args[0]);
+                return None;
+            }
+            options.insert(ch, a.starts_with("-"));
+        }
+        *pos += 1;
+    }
+    Some(options)
+}
+
+pub fn declare(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut pos = 1;
+    let mut options = match parse_options(args, &mut pos) {
+        Some(opts) => opts,
+        None       => return 2,
+    };
+
+    if options.get(&'p') == Some(&true) || (options.is_empty() && pos == args.len()) {
+        if pos == args.len() {
+            return print_all(core);
+        }
+
+        let mut exit_status = 0;
+        for a in &args[pos..] {
+            if ! print_definition(a, core) {
+                exit_status = 1;
+            }
+        }
+        return exit_status;
+    }
+
+    let layer = match options.get(&'g') == Some(&true) || core.data.get_layer_num() <= 2 {
+        true  => 0,
+        false => core.data.get_layer_num() - 2, //The last element is for declare itself.
+    };
+    options.remove(&'g');
+    options.remove(&'p');
+
+    let mut exit_status = 0;
+    for a in &args[pos..] {
+        if ! declare_one(&args[0], a, &options, core, layer) {
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
@@ -0,0 +1,78 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use super::printf;
+use nix::errno::Errno;
+use nix::unistd;
+use std::io;
+use std::io::Write;
+
+/* io::stdout() silently ignores a closed fd 1 (EBADF) */
+fn write_stdout(out: &[u8]) -> Result<(), Errno> {
+    let _ = io::stdout().flush();
+    let mut pos = 0;
+    while pos < out.len() {
+        match unistd::write(io::stdout(), &out[pos..]) {
+            Ok(n) => pos += n,
+            Err(Errno::EINTR) => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+pub fn echo(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut newline = true;
+    let mut escape = core.shopts.query("xpg_echo");
+
+    let mut pos = 1;
+    while pos < args.len() {
+        let a = &args[pos];
+        if a.len() < 2 || ! a.starts_with("-") || ! a[1..].chars().all(|c| "neE".contains(c)) {
+            break;
+        }
+
+        for c in a[1..].chars() {
+            match c {
+                'n' => newline = false,
+                'e' => escape = true,
+                _   => escape = false,
+            }
+        }
+        pos += 1;
+    }
+
+    let mut out = vec![];
+    for (i, a) in args[pos..].iter().enumerate() {
+        if i > 0 {
+            out.push(b' ');
+        }
+
+        if ! escape {
+            out.extend(a.as_bytes());
+            continue;
+        }
+
+        let (s, cont) = printf::expand_escapes(a, true);
+        out.extend(s);
+        if ! cont {
+            newline = false;
+            break;
+        }
+    }
+
+    if newline {
+        out.push(b'\n');
+    }
+
+    match write_stdout(&out) {
+        Ok(_)  => 0,
+        Err(e) => {
+            let msg = io::Error::from_raw_os_error(e as i32).to_string();
+            let msg = msg.split(" (os error").next().unwrap_or_default();
+            eprintln!("sush: echo: write error: {}", msg);
+            1
+        },
+    }
+}
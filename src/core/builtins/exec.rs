@@ -0,0 +1,117 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use nix::errno::Errno;
+use nix::unistd;
+use std::env;
+use std::ffi::CString;
+
+struct ExecOption {
+    name: Option<String>,
+    clear_env: bool,
+    login: bool,
+}
+
+fn usage() -> i32 {
+    eprintln!("exec: usage: exec [-cl] [-a name] [command [argument ...]] [redirection ...]");
+    2
+}
+
+fn parse_options(args: &[String], eo: &mut ExecOption) -> Result<usize, i32> {
+    let mut pos = 1;
+    while pos < args.len() {
+        let arg = &args[pos];
+        if arg == "--" {
+            return Ok(pos + 1);
+        }
+        if ! arg.starts_with("-") || arg == "-" {
+            break;
+        }
+
+        for (i, opt) in arg.char_indices().skip(1) {
+            match opt {
+                'c' => eo.clear_env = true,
+                'l' => eo.login = true,
+                'a' => {
+                    let value = match &arg[i+1..] {
+                        "" => {
+                            pos += 1;
+                            match args.get(pos) {
+                                Some(v) => v.clone(),
+                                None => {
+                                    eprintln!("sush: exec: -a: option requires an argument");
+                                    return Err(usage());
+                                },
+                            }
+                        },
+                        v => v.to_string(),
+                    };
+                    eo.name = Some(value);
+                    break;
+                },
+                _ => {
+                    eprintln!("sush: exec: -{}: invalid option", opt);
+                    return Err(usage());
+                },
+            }
+        }
+        pos += 1;
+    }
+    Ok(pos)
+}
+
+fn to_cstring(s: &str) -> CString {
+    CString::new(s.to_string()).unwrap_or_default()
+}
+
+/* redirections without a command are kept by SimpleCommand */
+pub fn exec(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut eo = ExecOption { name: None, clear_env: false, login: false };
+    let pos = match parse_options(args, &mut eo) {
+        Ok(n)  => n,
+        Err(e) => return e,
+    };
+
+    if pos >= args.len() {
+        return 0;
+    }
+
+    let mut argv0 = eo.name.clone().unwrap_or(args[pos].clone());
+    if eo.login {
+        argv0.insert(0, '-');
+    }
+    let mut cargs = vec![to_cstring(&argv0)];
+    cargs.extend(args[pos+1..].iter().map(|a| to_cstring(a)));
+
+    core.set_environment_variables();
+    if eo.clear_env {
+        for (k, _) in env::vars() {
+            env::remove_var(k);
+        }
+    }
+    core.set_ignored_signals(false);
+
+    let status = match unistd::execvp(&to_cstring(&args[pos]), &cargs) {
+        Err(Errno::EACCES) => {
+            eprintln!("sush: exec: {}: Permission denied", &args[pos]);
+            126
+        },
+        Err(Errno::ENOENT) => {
+            eprintln!("sush: exec: {}: not found", &args[pos]);
+            127
+        },
+        Err(err) => {
+            eprintln!("sush: exec: {}: {}", &args[pos], err.desc());
+            126
+        },
+        _ => 127,
+    };
+
+    if ! core.data.flags.contains('i') {
+        core.data.set_param("?", &status.to_string());
+        core.exit();
+    }
+    core.set_ignored_signals(true);
+    status
+}
@@ -0,0 +1,135 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::utils::file_check;
+
+fn print_table(core: &mut ShellCore, reusable: bool) -> i32 {
+    if core.data.hash_table.is_empty() {
+        println!("hash: hash table empty");
+        return 0;
+    }
+
+    let mut names: Vec<&String> = core.data.hash_table.keys().collect();
+    names.sort();
+
+    if ! reusable {
+        println!("hits\tcommand");
+    }
+    for name in names {
+        let (hits, path) = &core.data.hash_table[name];
+        match reusable {
+            true  => println!("builtin hash -p {} {}", path, name),
+            false => println!("{:4}\t{}", hits, path),
+        }
+    }
+    0
+}
+
+fn print_paths(core: &mut ShellCore, names: &[String]) -> i32 {
+    let mut exit_status = 0;
+    for name in names {
+        match (core.data.hash_table.get(name), names.len()) {
+            (Some((_, path)), 1) => println!("{}", path),
+            (Some((_, path)), _) => println!("{}\t{}", name, path),
+            (None, _) => {
+                eprintln!("sush: hash: {}: not found", name);
+                exit_status = 1;
+            },
+        }
+    }
+    exit_status
+}
+
+fn usage() -> i32 {
+    eprintln!("hash: usage: hash [-lr] [-p pathname] [-dt] [name ...]");
+    2
+}
+
+pub fn hash(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if ! core.data.flags.contains('h') {
+        eprintln!("sush: hash: hashing disabled");
+        return 1;
+    }
+
+    let (mut delete, mut reusable, mut show, mut clear) = (false, false, false, false);
+    let mut path = None;
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        for opt in args[pos][1..].to_string().chars() {
+            match opt {
+                'd' => delete = true,
+                'l' => reusable = true,
+                'r' => clear = true,
+                't' => show = true,
+                'p' => {
+                    pos += 1;
+                    match args.get(pos) {
+                        Some(p) => path = Some(p.clone()),
+                        None    => {
+                            eprintln!("sush: hash: -p: option requires an argument");
+                            return usage();
+                        },
+                    }
+                },
+                _ => {
+                    eprintln!("sush: hash: -{}: invalid option", opt);
+                    return usage();
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    if clear {
+        core.data.hash_table.clear();
+    }
+
+    let names = args[pos..].to_vec();
+    if names.is_empty() {
+        return match clear {
+            true  => 0,
+            false => print_table(core, reusable),
+        };
+    }
+
+    if show {
+        return print_paths(core, &names);
+    }
+
+    let mut exit_status = 0;
+    for name in names {
+        if delete {
+            if core.data.hash_table.remove(&name).is_none() {
+                eprintln!("sush: hash: {}: not found", &name);
+                exit_status = 1;
+            }
+            continue;
+        }
+
+        if let Some(p) = &path {
+            core.data.hash_table.insert(name, (0, p.clone()));
+            continue;
+        }
+
+        if name.contains('/') || core.builtins.contains_key(&name)
+        || core.data.functions.contains_key(&name) {
+            continue;
+        }
+
+        let path_var = core.data.get_param("PATH");
+        match file_check::search_command(&name, &path_var) {
+            Some(p) => {core.data.hash_table.insert(name, (0, p));},
+            None    => {
+                eprintln!("sush: hash: {}: not found", &name);
+                exit_status = 1;
+            },
+        }
+    }
+    exit_status
+}
@@ -3,10 +3,15 @@
 
 use crate::ShellCore;
 use crate::core::JobEntry;
-use crate::core::{ignore_signal, restore_signal};
+use crate::core::{ignore_signal, restore_signal, trap};
+use super::trap::print_signal_list;
+use nix::sys::signal;
 use nix::sys::signal::Signal;
 use nix::unistd;
 use nix::unistd::Pid;
+use std::str::FromStr;
+use std::{thread, time};
+use std::sync::atomic::Ordering::Relaxed;
 
 fn id_to_job(id: usize, jobs: &mut Vec<JobEntry>) -> Option<&mut JobEntry> {
     for job in jobs.iter_mut() {
@@ -18,37 +23,74 @@ fn id_to_job(id: usize, jobs: &mut Vec<JobEntry>) -> Option<&mut JobEntry> {
     None
 }
 
-fn arg_to_id(s: &str, priority: &Vec<usize>) -> usize {
-    if s == "%+" {
-        return match priority.len() {
-            0 => 0, 
-            _ => priority[0],
-        };
-    }
+/* %N, %+ (%%, %), %-, %string (prefix) and %?string (substring) */
+fn arg_to_id(s: &str, priority: &[usize], jobs: &[JobEntry]) -> Result<usize, String> {
+    let no_job = Err("no such job".to_string());
 
-    if s == "%-" {
-        return match priority.len() {
-            0 => 0, 
-            1 => 0, 
-            _ => priority[1],
-        };
+    let ids: Vec<usize> = if s == "%+" || s == "%%" || s == "%" {
+        priority.iter().take(1).cloned().collect()
+    }else if s == "%-" {
+        priority.iter().skip(1).take(1).cloned().collect()
+    }else if ! s.starts_with("%") {
+        return no_job;
+    }else if let Ok(n) = s[1..].parse::<usize>() {
+        jobs.iter().filter(|j| j.id == n).map(|j| j.id).collect()
+    }else if let Some(sub) = s.strip_prefix("%?") {
+        jobs.iter().filter(|j| j.text.contains(sub)).map(|j| j.id).collect()
+    }else{
+        jobs.iter().filter(|j| j.text.starts_with(&s[1..])).map(|j| j.id).collect()
+    };
+
+    match ids.len() {
+        0 => no_job,
+        1 => Ok(ids[0]),
+        _ => Err("ambiguous job spec".to_string()),
     }
+}
+
+fn pid_to_id(pid: &str, jobs: &[JobEntry]) -> usize {
+    let pid = match pid.parse::<i32>() {
+        Ok(n) => Pid::from_raw(n),
+        _     => return 0,
+    };
 
-    if s.starts_with("%") {
-        return s[1..].parse::<usize>().unwrap_or(0);
+    match jobs.iter().find(|j| j.pids.contains(&pid)) {
+        Some(job) => job.id,
+        None      => 0,
     }
+}
 
-    0
+fn remove_job(core: &mut ShellCore, id: usize) {
+    core.job_table.retain(|j| j.id != id);
+    core.job_table_priority.retain(|i| *i != id);
+}
+
+fn no_job_control(core: &mut ShellCore, com: &str) -> bool {
+    if core.data.flags.contains('m') {
+        return false;
+    }
+    eprintln!("sush: {}: no job control", com);
+    true
 }
 
 pub fn bg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if no_job_control(core, "bg") {
+        return 1;
+    }
+
     let id = if args.len() == 1 {
         if core.job_table_priority.len() == 0 {
             return 1;
         }
         core.job_table_priority[0]
     }else if args.len() == 2 {
-        arg_to_id(&args[1], &core.job_table_priority)
+        match arg_to_id(&args[1], &core.job_table_priority, &core.job_table) {
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!("sush: {}: {}: {}", &args[0], &args[1], e);
+                return 1;
+            },
+        }
     }else{
         return 1;
     };
@@ -61,6 +103,10 @@ pub fn bg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 }
 
 pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if no_job_control(core, "fg") {
+        return 1;
+    }
+
     let fd = match core.tty_fd.as_ref() {
         Some(fd) => fd,
         _        => return 1,
@@ -72,7 +118,13 @@ pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         }
         core.job_table_priority[0]
     }else if args.len() == 2 {
-        arg_to_id(&args[1], &core.job_table_priority)
+        match arg_to_id(&args[1], &core.job_table_priority, &core.job_table) {
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!("sush: {}: {}: {}", &args[0], &args[1], e);
+                return 1;
+            },
+        }
     }else{
         return 1;
     };
@@ -104,26 +156,408 @@ pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     exit_status
 }
 
-pub fn jobs(core: &mut ShellCore, _: &mut Vec<String>) -> i32 {
-    for job in core.job_table.iter() {
-        job.print(&core.job_table_priority);
+pub fn jobs(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut with_pid, mut pid_only, mut running, mut stopped) = (false, false, false, false);
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        for opt in args[pos][1..].chars() {
+            match opt {
+                'l' => with_pid = true,
+                'p' => pid_only = true,
+                'r' => running = true,
+                's' => stopped = true,
+                _ => {
+                    eprintln!("sush: jobs: -{}: invalid option", opt);
+                    eprintln!("jobs: usage: jobs [-lnprs] [jobspec ...]");
+                    return 2;
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    core.jobtable_check_status();
+    let mut exit_status = 0;
+    let ids = match pos < args.len() {
+        true  => {
+            let mut ids = vec![];
+            for arg in &args[pos..] {
+                match arg_to_id(arg, &core.job_table_priority, &core.job_table) {
+                    Ok(id) => ids.push(id),
+                    Err(e) => {
+                        eprintln!("sush: jobs: {}: {}", arg, e);
+                        exit_status = 1;
+                    },
+                }
+            }
+            ids
+        },
+        false => core.job_table.iter().map(|j| j.id).collect(),
+    };
+
+    for job in core.job_table.iter().filter(|j| ids.contains(&j.id)) {
+        if running && job.display_status != "Running"
+        || stopped && job.display_status != "Stopped" {
+            continue;
+        }
+
+        match (pid_only, job.pids.first()) {
+            (true, Some(pid)) => println!("{}", pid),
+            (true, None) => {},
+            (false, _) => job.print(&core.job_table_priority, with_pid),
+        }
+    }
+    exit_status
+}
+
+fn wait_usage() -> i32 {
+    eprintln!("wait: usage: wait [-fn] [-p var] [id ...]");
+    2
+}
+
+fn wait_job(core: &mut ShellCore, id: usize, force: bool, var: &Option<String>) -> i32 {
+    let job = match id_to_job(id, &mut core.job_table) {
+        Some(job) => job,
+        None      => return 127,
+    };
+
+    let mut exit_status = job.update_status(true);
+    while force && job.display_status == "Stopped" {
+        exit_status = job.update_status(true);
+    }
+
+    let exit_status = job.exit_status().unwrap_or(exit_status);
+    let pid = job.pids.last().map(|p| p.to_string()).unwrap_or_default();
+    if job.display_status != "Stopped" {
+        core.jobtable_unset_coprocs();
+        remove_job(core, id);
+    }
+    if let Some(v) = var {
+        core.data.set_param(v, &pid);
+    }
+    exit_status
+}
+
+/* wait -n */
+fn wait_next(core: &mut ShellCore, ids: &[usize], var: &Option<String>) -> i32 {
+    loop {
+        let mut waiting = false;
+        for job in core.job_table.iter_mut() {
+            if ! ids.is_empty() && ! ids.contains(&job.id) {
+                continue;
+            }
+            waiting = true;
+            job.update_status(false);
+
+            if let Some(exit_status) = job.exit_status() {
+                let pid = job.pids.last().map(|p| p.to_string()).unwrap_or_default();
+                let id = job.id;
+                core.jobtable_unset_coprocs();
+                remove_job(core, id);
+                if let Some(v) = var {
+                    core.data.set_param(v, &pid);
+                }
+                return exit_status;
+            }
+        }
+
+        if ! waiting {
+            return 127;
+        }
+        if core.sigint.load(Relaxed) {
+            return 130;
+        }
+        thread::sleep(time::Duration::from_millis(10));
     }
-    0
 }
 
 pub fn wait(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
+    let (mut next, mut force, mut var) = (false, false, None);
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        let arg = args[pos].clone();
+        for (i, opt) in arg.char_indices().skip(1) {
+            match opt {
+                'n' => next = true,
+                'f' => force = true,
+                'p' => {
+                    var = match &arg[i+1..] {
+                        "" => {
+                            pos += 1;
+                            match args.get(pos) {
+                                Some(v) => Some(v.clone()),
+                                None => {
+                                    eprintln!("sush: wait: -p: option requires an argument");
+                                    return wait_usage();
+                                },
+                            }
+                        },
+                        v => Some(v.to_string()),
+                    };
+                    break;
+                },
+                _ => {
+                    eprintln!("sush: wait: -{}: invalid option", opt);
+                    return wait_usage();
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    let mut ids = vec![];
+    for arg in &args[pos..] {
+        let id = match arg.starts_with("%") {
+            true  => arg_to_id(arg, &core.job_table_priority, &core.job_table),
+            false => Ok(pid_to_id(arg, &core.job_table)),
+        };
+
+        let id = match id {
+            Ok(0) => {
+                eprintln!("sush: wait: pid {} is not a child of this shell", arg);
+                0
+            },
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!("sush: wait: {}: {}", arg, e);
+                0
+            },
+        };
+        ids.push(id);
+    }
+
+    let exit_status = wait_ids(core, &ids, next, force, &var);
+    core.jobtable_unset_coprocs();
+    exit_status
+}
+
+fn wait_ids(core: &mut ShellCore, ids: &[usize], next: bool,
+            force: bool, var: &Option<String>) -> i32 {
+    if next {
+        return wait_next(core, ids, var);
+    }
+
+    if ids.is_empty() {
         for job in core.job_table.iter_mut() {
             job.update_status(true);
         }
         return 0;
     }
 
-    let id = arg_to_id(&args[1], &core.job_table_priority);
-    match id_to_job(id, &mut core.job_table) {
-        Some(job) => {job.update_status(true);},
-        _ => return 1, 
+    let mut exit_status = 0;
+    for id in ids {
+        exit_status = wait_job(core, *id, force, var);
+    }
+    exit_status
+}
+
+/* None means signal 0, which only checks the existence of processes */
+fn to_signal(spec: &str) -> Option<Option<Signal>> {
+    if spec == "0" {
+        return Some(None);
     }
 
-    0
+    match trap::to_signal_name(spec) {
+        Some(name) => Signal::from_str(&name).ok().map(Some),
+        None       => None,
+    }
+}
+
+fn kill_usage() -> i32 {
+    eprintln!("kill: usage: kill [-s sigspec | -n signum | -sigspec] pid | jobspec ... or kill -l [sigspec]");
+    2
+}
+
+fn print_signals(specs: &[String]) -> i32 {
+    if specs.is_empty() {
+        return print_signal_list();
+    }
+
+    let mut exit_status = 0;
+    for spec in specs {
+        let num = spec.parse::<i32>().map(|n| if n > 128 { n - 128 } else { n });
+        let ans = match num {
+            Ok(n) => Signal::try_from(n).ok().map(|s| s.as_str()[3..].to_string()),
+            _     => to_signal(spec).flatten().map(|s| (s as i32).to_string()),
+        };
+
+        match ans {
+            Some(a) => println!("{}", a),
+            None    => {
+                eprintln!("sush: kill: {}: invalid signal specification", spec);
+                exit_status = 1;
+            },
+        }
+    }
+    exit_status
+}
+
+fn kill_target(core: &mut ShellCore, target: &str, sig: Option<Signal>) -> bool {
+    let result = match target.starts_with("%") {
+        true  => {
+            let id = match arg_to_id(target, &core.job_table_priority, &core.job_table) {
+                Ok(id) => id,
+                Err(e) => {
+                    eprintln!("sush: kill: {}: {}", target, e);
+                    return false;
+                },
+            };
+            let job = id_to_job(id, &mut core.job_table).unwrap();
+            let result = job.send_signal(sig);
+            if job.display_status == "Stopped" && sig.is_some() && sig != Some(Signal::SIGCONT) {
+                let _ = job.send_signal(Some(Signal::SIGCONT));
+            }
+            result
+        },
+        false => match target.parse::<i32>() {
+            Ok(n) => signal::kill(Pid::from_raw(n), sig),
+            _ => {
+                eprintln!("sush: kill: {}: arguments must be process or job IDs", target);
+                return false;
+            },
+        },
+    };
+
+    if let Err(e) = result {
+        eprintln!("sush: kill: ({}) - {}", target, e.desc());
+        return false;
+    }
+    true
+}
+
+pub fn kill(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut args = args[1..].to_vec();
+    let mut sig = Some(Signal::SIGTERM);
+
+    match args.first().map(|a| a.as_str()) {
+        None => return kill_usage(),
+        Some("-l") | Some("-L") => return print_signals(&args[1..]),
+        Some("--") => {},
+        Some(opt) if opt == "-s" || opt == "-n" || ( opt.starts_with("-") && opt.len() > 1 ) => {
+            let spec = match opt {
+                "-s" | "-n" => match args.get(1) {
+                    Some(s) => s.clone(),
+                    None => {
+                        eprintln!("sush: kill: {}: option requires an argument", opt);
+                        return kill_usage();
+                    },
+                },
+                _ => opt[1..].to_string(),
+            };
+            sig = match to_signal(&spec) {
+                Some(s) => s,
+                None => {
+                    eprintln!("sush: kill: {}: invalid signal specification", spec);
+                    return 1;
+                },
+            };
+            let num = if opt == "-s" || opt == "-n" { 2 } else { 1 };
+            args.drain(..num);
+        },
+        _ => {},
+    }
+
+    if args.first().map(|a| a.as_str()) == Some("--") {
+        args.remove(0);
+    }
+
+    if args.is_empty() {
+        return kill_usage();
+    }
+
+    let mut exit_status = 0;
+    for target in &args {
+        if ! kill_target(core, target, sig) {
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
+
+pub fn disown(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut all, mut running, mut no_hup) = (false, false, false);
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        for opt in args[pos][1..].chars() {
+            match opt {
+                'a' => all = true,
+                'h' => no_hup = true,
+                'r' => running = true,
+                _ => {
+                    eprintln!("sush: disown: -{}: invalid option", opt);
+                    eprintln!("disown: usage: disown [-h] [-ar] [jobspec ... | pid ...]");
+                    return 2;
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    let mut exit_status = 0;
+    let ids: Vec<usize> = if pos < args.len() {
+        let mut ids = vec![];
+        for arg in &args[pos..] {
+            let id = match arg.starts_with("%") {
+                true  => arg_to_id(arg, &core.job_table_priority, &core.job_table),
+                false => Ok(pid_to_id(arg, &core.job_table)),
+            };
+            match id {
+                Ok(0) => {
+                    eprintln!("sush: disown: {}: no such job", arg);
+                    exit_status = 1;
+                },
+                Ok(id) => ids.push(id),
+                Err(e) => {
+                    eprintln!("sush: disown: {}: {}", arg, e);
+                    exit_status = 1;
+                },
+            }
+        }
+        ids
+    }else if all || running {
+        core.job_table.iter()
+            .filter(|j| ! running || j.display_status == "Running")
+            .map(|j| j.id).collect()
+    }else{
+        match core.job_table_priority.first() {
+            Some(id) => vec![*id],
+            None => {
+                eprintln!("sush: disown: current: no such job");
+                return 1;
+            },
+        }
+    };
+
+    for id in ids {
+        match no_hup {
+            true  => if let Some(job) = id_to_job(id, &mut core.job_table) {
+                job.no_hup = true;
+            },
+            false => remove_job(core, id),
+        }
+    }
+    exit_status
+}
+
+pub fn suspend(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() > 1 && args[1] != "-f" {
+        eprintln!("sush: suspend: {}: invalid option", &args[1]);
+        eprintln!("suspend: usage: suspend [-f]");
+        return 2;
+    }
+
+    if ! core.data.flags.contains('m') && ! core.data.flags.contains('i') {
+        eprintln!("sush: suspend: cannot suspend: no job control");
+        return 1;
+    }
+
+    if core.shopts.query("login_shell") && args.len() == 1 {
+        eprintln!("sush: suspend: cannot suspend a login shell");
+        return 1;
+    }
+
+    match signal::kill(unistd::getpid(), Signal::SIGSTOP) {
+        Ok(_)  => 0,
+        Err(_) => 1,
+    }
 }
@@ -1,26 +1,8 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{error_message, ShellCore, Feeder};
-use crate::core::data::Value;
-use crate::elements::substitution::Substitution;
-
-fn set(arg: &str, core: &mut ShellCore, layer: usize) -> bool {
-    let mut sub = match Substitution::parse(&mut Feeder::new(arg), core) {
-        Some(s) => s,
-        _ => {
-            eprintln!("sush: local: `{}': not a valid identifier", arg);
-            return false;
-        },
-    };
-
-    match sub.eval(core) {
-        Value::EvaluatedSingle(s) => core.data.set_layer_param(&sub.key, &s, layer),
-        Value::EvaluatedArray(a)  => core.data.set_layer_array(&sub.key, &a, layer),
-        _ => error_message::internal("unsupported substitution"),
-    }
-    true
-}
+use crate::ShellCore;
+use super::declare;
 
 pub fn local(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     let layer = if core.data.get_layer_num() > 2 {
@@ -30,8 +12,19 @@ pub fn local(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         return 1;
     };
 
-    match args[1..].iter().all(|a| set(a, core, layer)) {
-        true  => 0,
-        false => 1,
+    let mut pos = 1;
+    let mut options = match declare::parse_options(args, &mut pos) {
+        Some(opts) => opts,
+        None       => return 2,
+    };
+    options.remove(&'g');
+    options.remove(&'p');
+
+    let mut exit_status = 0;
+    for a in &args[pos..] {
+        if ! declare::declare_one("local", a, &options, core, layer) {
+            exit_status = 1;
+        }
     }
+    exit_status
 }
@@ -0,0 +1,227 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{Feeder, Script, ShellCore};
+use nix::{fcntl, unistd};
+use std::os::fd::RawFd;
+use super::utils;
+
+struct MapfileOption {
+    delim: u8,
+    count: usize,
+    origin: Option<usize>,
+    skip: usize,
+    trim: bool,
+    fd: RawFd,
+    callback: Option<String>,
+    quantum: usize,
+}
+
+impl MapfileOption {
+    fn new() -> Self {
+        MapfileOption {
+            delim: b'\n',
+            count: 0,
+            origin: None,
+            skip: 0,
+            trim: false,
+            fd: 0,
+            callback: None,
+            quantum: 5000,
+        }
+    }
+}
+
+/* reads byte by byte when a line count is given
+   so as not to consume the input after the last line */
+struct Reader {
+    fd: RawFd,
+    buf: Vec<u8>,
+    pos: usize,
+    bytewise: bool,
+}
+
+impl Reader {
+    fn fill(&mut self) -> bool {
+        let mut tmp = vec![0; if self.bytewise {1}else{4096}];
+        loop {
+            match unistd::read(self.fd, &mut tmp) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.buf = tmp[..n].to_vec();
+                    self.pos = 0;
+                    return true;
+                },
+                Err(nix::errno::Errno::EINTR) => continue,
+                Err(_) => return false,
+            }
+        }
+    }
+
+    fn next_line(&mut self, delim: u8) -> Option<Vec<u8>> {
+        let mut line = vec![];
+        loop {
+            if self.pos >= self.buf.len() && ! self.fill() {
+                return match line.is_empty() {
+                    true  => None,
+                    false => Some(line),
+                };
+            }
+
+            let b = self.buf[self.pos];
+            self.pos += 1;
+            line.push(b);
+            if b == delim {
+                return Some(line);
+            }
+        }
+    }
+}
+
+fn usage(name: &str) -> i32 {
+    eprintln!("{}: usage: {} [-d delim] [-n count] [-O origin] [-s count] [-t] [-u fd] [-C callback] [-c quantum] [array]", name, name);
+    2
+}
+
+fn parse_num(value: &str, msg: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) => Ok(n),
+        _     => Err(format!("{}: {}", value, msg)),
+    }
+}
+
+fn set_option(opt: char, value: &str, mo: &mut MapfileOption) -> Result<(), String> {
+    match opt {
+        'd' => mo.delim = value.bytes().next().unwrap_or(b'\0'),
+        'n' => mo.count = parse_num(value, "invalid line count")?,
+        'O' => mo.origin = Some(parse_num(value, "invalid array origin")?),
+        's' => mo.skip = parse_num(value, "invalid line count")?,
+        'u' => {
+            mo.fd = match value.parse::<RawFd>() {
+                Ok(fd) if fd >= 0 => fd,
+                _ => return Err(format!("{}: invalid file descriptor specification", value)),
+            };
+            if fcntl::fcntl(mo.fd, fcntl::F_GETFD).is_err() {
+                return Err(format!("{}: invalid file descriptor: Bad file descriptor", value));
+            }
+        },
+        'C' => mo.callback = Some(value.to_string()),
+        'c' => {
+            mo.quantum = parse_num(value, "invalid callback quantum")?;
+            if mo.quantum == 0 {
+                return Err(format!("{}: invalid callback quantum", value));
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+fn parse_options(args: &[String], mo: &mut MapfileOption) -> Result<usize, i32> {
+    let mut pos = 1;
+    while pos < args.len() {
+        let arg = &args[pos];
+        if arg == "--" {
+            return Ok(pos + 1);
+        }
+        if ! arg.starts_with("-") || arg == "-" {
+            break;
+        }
+
+        for (i, opt) in arg.char_indices().skip(1) {
+            match opt {
+                't' => mo.trim = true,
+                'd' | 'n' | 'O' | 's' | 'u' | 'C' | 'c' => {
+                    let value = match &arg[i+1..] {
+                        "" => {
+                            pos += 1;
+                            match args.get(pos) {
+                                Some(v) => v.clone(),
+                                None => {
+                                    eprintln!("sush: {}: -{}: option requires an argument", &args[0], opt);
+                                    return Err(usage(&args[0]));
+                                },
+                            }
+                        },
+                        v => v.to_string(),
+                    };
+
+                    if let Err(msg) = set_option(opt, &value, mo) {
+                        eprintln!("sush: {}: {}", &args[0], msg);
+                        return Err(1);
+                    }
+                    break;
+                },
+                _ => {
+                    eprintln!("sush: {}: -{}: invalid option", &args[0], opt);
+                    return Err(usage(&args[0]));
+                },
+            }
+        }
+        pos += 1;
+    }
+    Ok(pos)
+}
+
+fn run_callback(callback: &str, index: usize, line: &str, core: &mut ShellCore) {
+    let command = format!("{} {} {}", callback, index, utils::single_quote(line));
+    let mut feeder = Feeder::new(&command);
+    if let Some(mut s) = Script::parse(&mut feeder, core, false) {
+        s.exec(core);
+    }
+}
+
+pub fn mapfile(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut mo = MapfileOption::new();
+    let name = match parse_options(args, &mut mo) {
+        Ok(n) => args.get(n).cloned().unwrap_or("MAPFILE".to_string()),
+        Err(e) => return e,
+    };
+
+    if ! utils::is_varname(&name) {
+        eprintln!("sush: {}: `{}': not a valid identifier", &args[0], &name);
+        return 1;
+    }
+    if core.data.is_readonly(&name) {
+        eprintln!("sush: {}: readonly variable", &name);
+        return 1;
+    }
+
+    let origin = match mo.origin {
+        Some(o) => o,
+        None    => {
+            core.data.set_array(&name, &vec![]);
+            0
+        },
+    };
+
+    let mut reader = Reader { fd: mo.fd, buf: vec![], pos: 0, bytewise: mo.count > 0 };
+    for _ in 0..mo.skip {
+        if reader.next_line(mo.delim).is_none() {
+            break;
+        }
+    }
+
+    let mut num = 0;
+    while mo.count == 0 || num < mo.count {
+        let mut line = match reader.next_line(mo.delim) {
+            Some(l) => l,
+            None    => break,
+        };
+        if mo.trim && line.last() == Some(&mo.delim) {
+            line.pop();
+        }
+        let line = String::from_utf8_lossy(&line).to_string();
+
+        let index = origin + num;
+        num += 1;
+        if let Some(callback) = &mo.callback {
+            if num % mo.quantum == 0 {
+                run_callback(callback, index, &line, core);
+            }
+        }
+
+        core.data.set_layer_array_elem(&name, &index.to_string(), &line, 0);
+    }
+    0
+}
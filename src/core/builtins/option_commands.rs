@@ -3,6 +3,7 @@
 
 use crate::{error_message, ShellCore};
 use crate::core::data::Value;
+use crate::core::options::{Options, FLAG_OPTIONS};
 
 fn print_data(k: &str, core: &mut ShellCore) {
     match core.data.get_value(k) {
@@ -12,7 +13,7 @@ fn print_data(k: &str, core: &mut ShellCore) {
         Some(Value::EvaluatedArray(a)) => {
             let mut formatted = String::new();
             formatted += "(";
-            for (i, v) in a.iter().enumerate() {
+            for (i, v) in a.iter() {
                 formatted += &format!("[{}]=\"{}\" ", i, v).clone();
             }
             if formatted.ends_with(" ") {
@@ -21,6 +22,15 @@ fn print_data(k: &str, core: &mut ShellCore) {
             formatted += ")";
             println!("{}={}", k.to_string(), formatted); 
         },
+        Some(Value::AssocArray(a)) => {
+            let mut formatted = String::new();
+            formatted += "(";
+            for k in core.data.get_array_keys(k) {
+                formatted += &format!("[{}]=\"{}\" ", k, a[&k]).clone();
+            }
+            formatted += ")";
+            println!("{}={}", k.to_string(), formatted); 
+        },
         _ => {},
     }
 }
@@ -51,25 +61,86 @@ fn set_option(core: &mut ShellCore, opt: char, pm: char) {
     }
 }
 
+fn set_long_option(core: &mut ShellCore, name: &str, pm: char) -> bool {
+    if let Some(opt) = Options::flag_of(name) {
+        set_option(core, opt, pm);
+        return true;
+    }
+
+    if ! core.options.exist(name) {
+        eprintln!("sush: set: {}: invalid option name", name);
+        return false;
+    }
+    core.options.set(name, pm == '-')
+}
+
+fn get_all_options(core: &mut ShellCore) -> Vec<(String, bool)> {
+    let mut list = core.options.get_all();
+    for (opt, name) in FLAG_OPTIONS {
+        list.push( (name.to_string(), core.data.flags.contains(opt)) );
+    }
+    list.sort();
+    list
+}
+
+fn print_options(core: &mut ShellCore, pm: char) {
+    for (name, onoff) in get_all_options(core) {
+        match pm {
+            '-' => println!("{}", Options::format(&name, onoff)),
+            _   => println!("{}", Options::format2(&name, onoff)),
+        }
+    }
+}
+
+pub fn set_shellopts(core: &mut ShellCore) {
+    let opts = get_all_options(core).into_iter()
+               .filter(|opt| opt.1)
+               .map(|opt| opt.0)
+               .collect::<Vec<String>>();
+    core.data.set_param("SHELLOPTS", &opts.join(":"));
+}
+
+fn set_rest_parameters(core: &mut ShellCore, args: &[String]) -> i32 {
+    let mut params = vec![core.data.get_param("0")];
+    params.extend(args.iter().cloned());
+    set_parameters(core, &params)
+}
+
 fn set_options(core: &mut ShellCore, args: &[String]) -> i32 {
-    for a in args {
-        if a.starts_with("--") {
-            return 0;
+    let mut pos = 0;
+    while pos < args.len() {
+        let a = &args[pos];
+        if a == "--" {
+            return set_rest_parameters(core, &args[pos+1..]);
+        }
+        if ! a.starts_with("-") && ! a.starts_with("+") {
+            return set_rest_parameters(core, &args[pos..]);
         }
+
         let pm = a.chars().nth(0).unwrap();
         for ch in a[1..].chars() {
-            if "xve".find(ch).is_none() {
+            if ch == 'o' {
+                pos += 1;
+                match args.get(pos) {
+                    None => print_options(core, pm),
+                    Some(name) => if ! set_long_option(core, name, pm) {
+                        return 2;
+                    },
+                }
+            }else if Options::name_of(ch).is_some() {
+                set_option(core, ch, pm);
+            }else{
                 eprintln!("sush: set: {}{}: invalid option", &pm, &ch);
                 return 2;
             }
-            set_option(core, ch, pm);
         }
+        pos += 1;
     }
     0
 }
 
 pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    match args.len() {
+    let ans = match args.len() {
         0 => panic!("never come here"),
         1 => {
             match args[0] == "set" {
@@ -83,36 +154,15 @@ pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
                 return set_parameters(core, args)
             }
 
-            if args[1] == "-o" {
-                if args.len() == 2 {
-                    core.options.print_all();
-                    return 0;
-                }else{
-                    match core.options.set(&args[2], true) {
-                        true  => return 0,
-                        false => return 2,
-                    }
-                }
-            }
-
-            if args[1] == "+o" {
-                if args.len() == 2 {
-                    core.options.print_all2();
-                    return 0;
-                }else{
-                    match core.options.set(&args[2], false) {
-                        true  => return 0,
-                        false => return 2,
-                    }
-                }
-            }
-
             match args[1].starts_with("-") || args[1].starts_with("+") {
                 true  => set_options(core, &args[1..]),
                 false => set_parameters(core, args),
             }
         },
-    }
+    };
+
+    set_shellopts(core);
+    ans
 }
 
 pub fn shopt_print(core: &mut ShellCore, args: &mut Vec<String>, all: bool) -> i32 {
@@ -125,7 +175,9 @@ pub fn shopt_print(core: &mut ShellCore, args: &mut Vec<String>, all: bool) -> i
     match args[1].as_str() {
         "-s" => core.shopts.print_if(true),
         "-u" => core.shopts.print_if(false),
-        opt  => res = core.shopts.print_opt(opt),
+        _    => for opt in &args[1..] {
+            res &= core.shopts.print_opt(opt);
+        },
     }
 
     match res {
@@ -135,19 +187,14 @@ pub fn shopt_print(core: &mut ShellCore, args: &mut Vec<String>, all: bool) -> i
 }
 
 pub fn shopt(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() < 3 {
+    if args.len() < 3 || ( args[1] != "-s" && args[1] != "-u" ) {
         return shopt_print(core, args, args.len() < 2);
     }
 
-    let res = match args[1].as_str() {
-        "-s" => core.shopts.set(&args[2], true),
-        "-u" => core.shopts.set(&args[2], false),
-        arg  => {
-            eprintln!("sush: shopt: {}: invalid shell option name", arg);
-            eprintln!("shopt: usage: shopt [-su] [optname ...]");
-            false
-        },
-    };
+    let mut res = true;
+    for opt in &args[2..] {
+        res &= core.shopts.set(opt, args[1] == "-s");
+    }
 
     match res {
         true  => 0,
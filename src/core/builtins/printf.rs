@@ -0,0 +1,678 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::subscript::Subscript;
+use nix::libc;
+use std::ffi::CString;
+use std::io::Write;
+use super::utils;
+
+#[derive(Debug, Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+struct Printf {
+    args: Vec<String>,
+    pos: usize,
+    out: Vec<u8>,
+    exit_status: i32,
+    stop: bool,
+}
+
+fn oct_value(chars: &[char], pos: &mut usize, max: usize) -> u32 {
+    let mut ans = 0;
+    for _ in 0..max {
+        match chars.get(*pos).and_then(|c| c.to_digit(8)) {
+            Some(d) => ans = ans*8 + d,
+            None    => break,
+        }
+        *pos += 1;
+    }
+    ans
+}
+
+fn hex_value(chars: &[char], pos: &mut usize, max: usize) -> Option<u32> {
+    let start = *pos;
+    let mut ans = 0;
+    for _ in 0..max {
+        match chars.get(*pos).and_then(|c| c.to_digit(16)) {
+            Some(d) => ans = ans*16 + d,
+            None    => break,
+        }
+        *pos += 1;
+    }
+    match *pos == start {
+        true  => None,
+        false => Some(ans),
+    }
+}
+
+fn push_char(out: &mut Vec<u8>, c: char) {
+    let mut buf = [0; 4];
+    out.extend(c.encode_utf8(&mut buf).as_bytes());
+}
+
+#[derive(PartialEq)]
+enum Mode {
+    Format,
+    Arg, // %b
+    Echo,
+}
+
+/* chars[*pos] must be a backslash. Returns false at \c in %b arguments or echo. */
+fn escape(chars: &[char], pos: &mut usize, out: &mut Vec<u8>, mode: &Mode) -> bool {
+    let for_b = *mode != Mode::Format;
+    *pos += 1;
+    let c = match chars.get(*pos) {
+        Some(c) => *c,
+        None    => {
+            out.push(b'\\');
+            return true;
+        },
+    };
+    *pos += 1;
+
+    match c {
+        'a' => out.push(0x07),
+        'b' => out.push(0x08),
+        'e' | 'E' => out.push(0x1b),
+        'f' => out.push(0x0c),
+        'n' => out.push(b'\n'),
+        'r' => out.push(b'\r'),
+        't' => out.push(b'\t'),
+        'v' => out.push(0x0b),
+        '\\' => out.push(b'\\'),
+        '"' | '\'' if ! for_b => push_char(out, c),
+        'c' if for_b => return false,
+        '0' if for_b => out.push(oct_value(chars, pos, 3) as u8),
+        '1'..='7' if *mode == Mode::Echo => {
+            out.push(b'\\');
+            push_char(out, c);
+        },
+        '0'..='7' => {
+            *pos -= 1;
+            out.push(oct_value(chars, pos, 3) as u8);
+        },
+        'x' => match hex_value(chars, pos, 2) {
+            Some(n) => out.push(n as u8),
+            None    => out.extend(b"\\x"),
+        },
+        'u' | 'U' => {
+            let max = if c == 'u' { 4 } else { 8 };
+            match hex_value(chars, pos, max) {
+                Some(n) => push_char(out, char::from_u32(n).unwrap_or('\u{FFFD}')),
+                None    => push_char(out, c),
+            }
+        },
+        _ => {
+            out.push(b'\\');
+            push_char(out, c);
+        },
+    }
+    true
+}
+
+/* expands the escape sequences for %b and echo -e. The flag is false when \c appears. */
+pub fn expand_escapes(s: &str, for_echo: bool) -> (Vec<u8>, bool) {
+    let mode = match for_echo {
+        true  => Mode::Echo,
+        false => Mode::Arg,
+    };
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = vec![];
+    let mut pos = 0;
+    while pos < chars.len() {
+        if chars[pos] != '\\' {
+            push_char(&mut out, chars[pos]);
+            pos += 1;
+        }else if ! escape(&chars, &mut pos, &mut out, &mode) {
+            return (out, false);
+        }
+    }
+    (out, true)
+}
+
+pub fn quote(s: &str) -> String {
+    if s.is_empty() {
+        return "''".to_string();
+    }
+
+    if s.chars().any(|c| c.is_control()) {
+        let mut ans = "$'".to_string();
+        for c in s.chars() {
+            match c {
+                '\n' => ans += "\\n",
+                '\t' => ans += "\\t",
+                '\r' => ans += "\\r",
+                '\x1b' => ans += "\\E",
+                '\x07' => ans += "\\a",
+                '\x08' => ans += "\\b",
+                '\x0b' => ans += "\\v",
+                '\x0c' => ans += "\\f",
+                '\'' | '\\' => { ans.push('\\'); ans.push(c); },
+                c if c.is_control() => ans += &format!("\\{:03o}", c as u32),
+                c => ans.push(c),
+            }
+        }
+        return ans + "'";
+    }
+
+    let mut ans = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if " \t'\"\\|&;()<>!{}*[?]^$`,".contains(c)
+        || ( i == 0 && (c == '~' || c == '#') ) {
+            ans.push('\\');
+        }
+        ans.push(c);
+    }
+    ans
+}
+
+fn pad(spec: &Spec, sign: &str, body: &[u8], zero_ok: bool) -> Vec<u8> {
+    let len = sign.len() + body.len();
+    let fill = spec.width.saturating_sub(len);
+    let mut ans = vec![];
+
+    if spec.left {
+        ans.extend(sign.as_bytes());
+        ans.extend(body);
+        ans.extend(vec![b' '; fill]);
+    }else if spec.zero && zero_ok {
+        ans.extend(sign.as_bytes());
+        ans.extend(vec![b'0'; fill]);
+        ans.extend(body);
+    }else{
+        ans.extend(vec![b' '; fill]);
+        ans.extend(sign.as_bytes());
+        ans.extend(body);
+    }
+    ans
+}
+
+fn sign_str(negative: bool, spec: &Spec) -> &'static str {
+    match (negative, spec.plus, spec.space) {
+        (true, _, _) => "-",
+        (_, true, _) => "+",
+        (_, _, true) => " ",
+        _ => "",
+    }
+}
+
+fn format_e(v: f64, prec: usize, upper: bool) -> String {
+    let s = format!("{:.*e}", prec, v);
+    let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+    let exp = exp.parse::<i32>().unwrap_or(0);
+    let sign = if exp < 0 { '-' } else { '+' };
+    let e = if upper { 'E' } else { 'e' };
+    format!("{}{}{}{:02}", mantissa, e, sign, exp.abs())
+}
+
+fn strip_zeros(s: &str) -> String {
+    let (num, exp) = match s.find(['e', 'E']) {
+        Some(p) => (&s[..p], &s[p..]),
+        None    => (s, ""),
+    };
+    match num.contains('.') {
+        true  => num.trim_end_matches('0').trim_end_matches('.').to_string() + exp,
+        false => s.to_string(),
+    }
+}
+
+fn format_g(v: f64, prec: usize, alt: bool, upper: bool) -> String {
+    let p = if prec == 0 { 1 } else { prec };
+    let e = format!("{:.*e}", p-1, v);
+    let x = e.split_once('e').map(|t| t.1).unwrap_or("0").parse::<i64>().unwrap_or(0);
+
+    let ans = match (p as i64) > x && x >= -4 {
+        true  => format!("{:.*}", (p as i64 - 1 - x) as usize, v),
+        false => format_e(v, p-1, upper),
+    };
+
+    match alt {
+        true  => ans,
+        false => strip_zeros(&ans),
+    }
+}
+
+fn strftime(fmt: &str, time: i64, tz: &str) -> String {
+    match tz.is_empty() {
+        true  => std::env::remove_var("TZ"),
+        false => std::env::set_var("TZ", tz),
+    }
+
+    let cfmt = match CString::new(fmt) {
+        Ok(c) => c,
+        _     => return String::new(),
+    };
+
+    let mut buf = vec![0u8; 1024];
+    let len = unsafe {
+        let t: libc::time_t = time as libc::time_t;
+        let tm = libc::localtime(&t); //localtime_r doesn't reread TZ
+        if tm.is_null() {
+            return String::new();
+        }
+        libc::strftime(buf.as_mut_ptr() as *mut libc::c_char, buf.len(), cfmt.as_ptr(), tm)
+    };
+    String::from_utf8_lossy(&buf[..len]).to_string()
+}
+
+impl Printf {
+    fn next_arg(&mut self) -> Option<String> {
+        let ans = self.args.get(self.pos).cloned();
+        if ans.is_some() {
+            self.pos += 1;
+        }
+        ans
+    }
+
+    fn invalid_number(&mut self, s: &str) {
+        eprintln!("sush: printf: {}: invalid number", s);
+        self.exit_status = 1;
+    }
+
+    fn parse_int(&mut self, s: &str) -> i128 {
+        let t = s.trim_start();
+        if t.starts_with(['\'', '"']) {
+            return t.chars().nth(1).map(|c| c as i128).unwrap_or(0);
+        }
+
+        let (negative, t) = match t.chars().next() {
+            Some('-') => (true, &t[1..]),
+            Some('+') => (false, &t[1..]),
+            _ => (false, t),
+        };
+
+        let (radix, digits) = if t.starts_with("0x") || t.starts_with("0X") {
+            (16, &t[2..])
+        }else if t.starts_with('0') && t.len() > 1 {
+            (8, &t[1..])
+        }else{
+            (10, t)
+        };
+
+        let len = digits.find(|c: char| ! c.is_digit(radix)).unwrap_or(digits.len());
+        let mut ans = i128::from_str_radix(&digits[..len], radix).unwrap_or(0);
+        if negative {
+            ans = -ans;
+        }
+
+        if (len == 0 && radix != 8) || len < digits.len() {
+            self.invalid_number(s);
+        }else if ans > i64::MAX as i128 || ans < i64::MIN as i128 {
+            eprintln!("sush: printf: warning: {}: Numerical result out of range", s);
+            self.exit_status = 1;
+            ans = ans.clamp(i64::MIN as i128, i64::MAX as i128);
+        }
+        ans
+    }
+
+    fn parse_float(&mut self, s: &str) -> f64 {
+        let t = s.trim();
+        if t.starts_with(['\'', '"']) || t.starts_with("0x") || t.starts_with("0X") {
+            return self.parse_int(s) as f64;
+        }
+        match t.parse::<f64>() {
+            Ok(f) => f,
+            _ => {
+                self.invalid_number(s);
+                0.0
+            },
+        }
+    }
+
+    fn star_arg(&mut self) -> i128 {
+        match self.next_arg() {
+            Some(a) => self.parse_int(&a),
+            None    => 0,
+        }
+    }
+
+    fn int_arg(&mut self) -> i128 {
+        match self.next_arg() {
+            Some(a) if ! a.is_empty() => self.parse_int(&a),
+            _ => 0,
+        }
+    }
+
+    fn float_arg(&mut self) -> f64 {
+        match self.next_arg() {
+            Some(a) if ! a.is_empty() => self.parse_float(&a),
+            _ => 0.0,
+        }
+    }
+
+    fn format_int(&mut self, conv: char, spec: &Spec) -> Vec<u8> {
+        let n = self.int_arg();
+        let (negative, body) = match conv {
+            'd' | 'i' => (n < 0, n.unsigned_abs().to_string()),
+            _ => {
+                let u = n as i64 as u64;
+                match conv {
+                    'o' => (false, format!("{:o}", u)),
+                    'x' => (false, format!("{:x}", u)),
+                    'X' => (false, format!("{:X}", u)),
+                    _   => (false, u.to_string()),
+                }
+            },
+        };
+
+        let mut body = match spec.precision {
+            Some(0) if n == 0 => String::new(),
+            Some(p) if p > body.len() => "0".repeat(p - body.len()) + &body,
+            _ => body,
+        };
+
+        let mut sign = sign_str(negative, spec).to_string();
+        if ! "di".contains(conv) {
+            sign.clear();
+        }
+        if spec.alt && n != 0 {
+            match conv {
+                'o' if ! body.starts_with('0') => body.insert(0, '0'),
+                'x' => sign += "0x",
+                'X' => sign += "0X",
+                _ => {},
+            }
+        }
+
+        pad(spec, &sign, body.as_bytes(), spec.precision.is_none())
+    }
+
+    fn format_float(&mut self, conv: char, spec: &Spec) -> Vec<u8> {
+        let v = self.float_arg();
+        let prec = spec.precision.unwrap_or(6);
+        let upper = conv.is_ascii_uppercase();
+
+        let body = if v.is_nan() {
+            "nan".to_string()
+        }else if v.is_infinite() {
+            "inf".to_string()
+        }else{
+            match conv.to_ascii_lowercase() {
+                'e' => format_e(v.abs(), prec, upper),
+                'g' => format_g(v.abs(), prec, spec.alt, upper),
+                _   => format!("{:.*}", prec, v.abs()),
+            }
+        };
+
+        let body = match upper {
+            true  => body.to_uppercase(),
+            false => body,
+        };
+        let sign = sign_str(v.is_sign_negative() && ! v.is_nan(), spec);
+        pad(spec, sign, body.as_bytes(), v.is_finite())
+    }
+
+    fn format_string(&mut self, conv: char, spec: &Spec) -> Vec<u8> {
+        let arg = self.next_arg().unwrap_or_default();
+        let mut body = match conv {
+            'b' => {
+                let (s, cont) = expand_escapes(&arg, false);
+                self.stop = ! cont;
+                s
+            },
+            'q' => quote(&arg).into_bytes(),
+            'c' => arg.chars().next().map(|c| c.to_string()).unwrap_or_default().into_bytes(),
+            _   => arg.into_bytes(),
+        };
+
+        if let Some(p) = spec.precision {
+            body.truncate(p);
+        }
+        pad(spec, "", &body, false)
+    }
+
+    fn format_time(&mut self, fmt: &str, spec: &Spec, core: &mut ShellCore) -> Vec<u8> {
+        let now = || std::time::SystemTime::now()
+                      .duration_since(std::time::UNIX_EPOCH)
+                      .map(|d| d.as_secs() as i64).unwrap_or(0);
+
+        let time = match self.next_arg() {
+            Some(a) if ! a.is_empty() => self.parse_int(&a) as i64,
+            _ => -1,
+        };
+        let time = match time {
+            -1 => now(),
+            -2 => core.start_time,
+            t  => t,
+        };
+
+        let fmt = match fmt.is_empty() {
+            true  => "%X", //the default of bash
+            false => fmt,
+        };
+        let mut body = strftime(fmt, time, &core.data.get_param("TZ")).into_bytes();
+        if let Some(p) = spec.precision {
+            body.truncate(p);
+        }
+        pad(spec, "", &body, false)
+    }
+
+    fn read_spec(&mut self, chars: &[char], pos: &mut usize) -> Spec {
+        let mut spec = Spec::default();
+        while let Some(c) = chars.get(*pos) {
+            match c {
+                '-' => spec.left = true,
+                '0' => spec.zero = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alt = true,
+                '\'' => {},
+                _ => break,
+            }
+            *pos += 1;
+        }
+
+        if chars.get(*pos) == Some(&'*') {
+            *pos += 1;
+            let w = self.star_arg();
+            if w < 0 {
+                spec.left = true;
+            }
+            spec.width = w.unsigned_abs() as usize;
+        }else{
+            while let Some(d) = chars.get(*pos).and_then(|c| c.to_digit(10)) {
+                spec.width = spec.width*10 + d as usize;
+                *pos += 1;
+            }
+        }
+
+        if chars.get(*pos) == Some(&'.') {
+            *pos += 1;
+            let mut p = 0;
+            if chars.get(*pos) == Some(&'*') {
+                *pos += 1;
+                p = self.star_arg().max(0) as usize;
+            }else{
+                while let Some(d) = chars.get(*pos).and_then(|c| c.to_digit(10)) {
+                    p = p*10 + d as usize;
+                    *pos += 1;
+                }
+            }
+            spec.precision = Some(p);
+        }
+
+        while let Some(c) = chars.get(*pos) {
+            match "hlLjzt".contains(*c) {
+                true  => *pos += 1,
+                false => break,
+            }
+        }
+        spec
+    }
+
+    fn conversion(&mut self, chars: &[char], pos: &mut usize, core: &mut ShellCore) -> bool {
+        let start = *pos;
+        *pos += 1;
+        if chars.get(*pos) == Some(&'%') {
+            *pos += 1;
+            self.out.push(b'%');
+            return true;
+        }
+
+        let spec = self.read_spec(chars, pos);
+
+        if chars.get(*pos) == Some(&'(') {
+            let rest: String = chars[*pos+1..].iter().collect();
+            if let Some(end) = rest.find(")T") {
+                let fmt = rest[..end].to_string();
+                *pos += 1 + fmt.chars().count() + 2;
+                let s = self.format_time(&fmt, &spec, core);
+                self.out.extend(s);
+                return true;
+            }
+        }
+
+        let conv = match chars.get(*pos) {
+            Some(c) => *c,
+            None    => {
+                let s: String = chars[start..].iter().collect();
+                eprintln!("sush: printf: `{}': missing format character", s);
+                self.exit_status = 1;
+                return false;
+            },
+        };
+        *pos += 1;
+
+        let s = match conv {
+            'd' | 'i' | 'u' | 'o' | 'x' | 'X' => self.format_int(conv, &spec),
+            'f' | 'F' | 'e' | 'E' | 'g' | 'G' => self.format_float(conv, &spec),
+            's' | 'b' | 'q' | 'c' => self.format_string(conv, &spec),
+            _ => {
+                eprintln!("sush: printf: `{}': invalid format character", conv);
+                self.exit_status = 1;
+                return false;
+            },
+        };
+        self.out.extend(s);
+        ! self.stop
+    }
+
+    fn run_format(&mut self, format: &[char], core: &mut ShellCore) -> bool {
+        let mut pos = 0;
+        while pos < format.len() {
+            match format[pos] {
+                '\\' => { escape(format, &mut pos, &mut self.out, &Mode::Format); },
+                '%' => if ! self.conversion(format, &mut pos, core) {
+                    return false;
+                },
+                c => {
+                    push_char(&mut self.out, c);
+                    pos += 1;
+                },
+            }
+        }
+        true
+    }
+}
+
+fn set_variable(name: &str, value: &str, core: &mut ShellCore) -> i32 {
+    let (var, sub) = match name.find('[') {
+        Some(p) if name.ends_with(']') => (&name[..p], Some(&name[p..])),
+        _ => (name, None),
+    };
+
+    if ! utils::is_varname(var) {
+        eprintln!("sush: printf: `{}': not a valid identifier", name);
+        return 2;
+    }
+    if core.data.is_readonly(var) {
+        eprintln!("sush: printf: {}: readonly variable", var);
+        return 1;
+    }
+
+    let layer = core.data.get_var_layer(var);
+    let sub = match sub {
+        Some(s) => s,
+        None    => {
+            core.data.set_layer_param(var, value, layer);
+            return 0;
+        },
+    };
+
+    let pos = Subscript::parse(&mut Feeder::new(sub), core)
+              .and_then(|mut s| s.eval(core, var));
+    match pos {
+        Some(p) if core.data.set_layer_array_elem(var, &p, value, layer) => 0,
+        _ => {
+            eprintln!("sush: printf: {}: bad array subscript", name);
+            1
+        },
+    }
+}
+
+pub fn printf(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut pos = 1;
+    let mut var = None;
+    while pos < args.len() {
+        match args[pos].as_str() {
+            "-v" => {
+                if pos+1 >= args.len() {
+                    eprintln!("sush: printf: -v: option requires an argument");
+                    eprintln!("printf: usage: printf [-v var] format [arguments]");
+                    return 2;
+                }
+                var = Some(args[pos+1].clone());
+                pos += 2;
+            },
+            "--" => {
+                pos += 1;
+                break;
+            },
+            a if a.starts_with('-') && a.len() > 1 => {
+                eprintln!("sush: printf: {}: invalid option", a);
+                eprintln!("printf: usage: printf [-v var] format [arguments]");
+                return 2;
+            },
+            _ => break,
+        }
+    }
+
+    if pos >= args.len() {
+        eprintln!("printf: usage: printf [-v var] format [arguments]");
+        return 2;
+    }
+
+    let format: Vec<char> = args[pos].chars().collect();
+    let mut p = Printf {
+        args: args[pos+1..].to_vec(),
+        pos: 0,
+        out: vec![],
+        exit_status: 0,
+        stop: false,
+    };
+
+    loop {
+        let before = p.pos;
+        if ! p.run_format(&format, core)
+        || p.pos >= p.args.len() || p.pos == before {
+            break;
+        }
+    }
+
+    match var {
+        Some(name) => {
+            let s = set_variable(&name, &String::from_utf8_lossy(&p.out), core);
+            match s {
+                0 => p.exit_status,
+                _ => s,
+            }
+        },
+        None => {
+            let mut stdout = std::io::stdout();
+            if stdout.write_all(&p.out).is_err() || stdout.flush().is_err() {
+                return 1;
+            }
+            p.exit_status
+        },
+    }
+}
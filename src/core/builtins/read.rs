@@ -2,59 +2,370 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
+use crate::elements::subword;
+use nix::{fcntl, libc, unistd};
+use nix::sys::termios;
+use nix::sys::termios::{LocalFlags, SetArg, SpecialCharacterIndices};
+use std::io::Write;
+use std::os::fd::{BorrowedFd, RawFd};
+use std::time::{Duration, Instant};
+use super::utils;
 
-fn is_varname(s :&String) -> bool {
-    if s.len() == 0 {
-        return false;
+/* a character and whether it was escaped by a backslash */
+type InputChar = (char, bool);
+
+enum End {
+    Delim,
+    Eof,
+    Timeout,
+}
+
+struct ReadOption {
+    raw: bool,
+    silent: bool,
+    prompt: Option<String>,
+    array: Option<String>,
+    delim: char,
+    nchars: Option<usize>,
+    exact: bool,
+    timeout: Option<f64>,
+    fd: RawFd,
+}
+
+impl ReadOption {
+    fn new() -> Self {
+        ReadOption {
+            raw: false,
+            silent: false,
+            prompt: None,
+            array: None,
+            delim: '\n',
+            nchars: None,
+            exact: false,
+            timeout: None,
+            fd: 0,
+        }
     }
+}
 
-    let first_ch = s.chars().nth(0).unwrap();
+fn usage() -> i32 {
+    eprintln!("read: usage: read [-rs] [-a array] [-d delim] [-n nchars] [-N nchars] [-p prompt] [-t timeout] [-u fd] [name ...]");
+    2
+}
 
-    if '0' <= first_ch && first_ch <= '9' {
+fn set_option(opt: char, value: &str, ro: &mut ReadOption) -> Result<(), String> {
+    match opt {
+        'p' => ro.prompt = Some(value.to_string()),
+        'a' => ro.array = Some(value.to_string()),
+        'd' => ro.delim = value.chars().next().unwrap_or('\0'),
+        'n' | 'N' => {
+            ro.nchars = match value.parse::<usize>() {
+                Ok(n) => Some(n),
+                _     => return Err(format!("{}: invalid number", value)),
+            };
+            ro.exact = opt == 'N';
+        },
+        't' => {
+            ro.timeout = match value.parse::<f64>() {
+                Ok(t) if t >= 0.0 => Some(t),
+                _ => return Err(format!("{}: invalid timeout specification", value)),
+            };
+        },
+        'u' => {
+            ro.fd = match value.parse::<RawFd>() {
+                Ok(fd) if fd >= 0 => fd,
+                _ => return Err(format!("{}: invalid file descriptor specification", value)),
+            };
+            if fcntl::fcntl(ro.fd, fcntl::F_GETFD).is_err() {
+                return Err(format!("{}: invalid file descriptor: Bad file descriptor", value));
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+fn parse_options(args: &[String], ro: &mut ReadOption) -> Result<usize, i32> {
+    let mut pos = 0;
+    while pos < args.len() {
+        let arg = &args[pos];
+        if arg == "--" {
+            return Ok(pos + 1);
+        }
+        if ! arg.starts_with("-") || arg == "-" {
+            break;
+        }
+
+        for (i, opt) in arg.char_indices().skip(1) {
+            match opt {
+                'r' => ro.raw = true,
+                's' => ro.silent = true,
+                'p' | 'a' | 'd' | 'n' | 'N' | 't' | 'u' => {
+                    let value = match &arg[i+1..] {
+                        "" => {
+                            pos += 1;
+                            match args.get(pos) {
+                                Some(v) => v.clone(),
+                                None => {
+                                    eprintln!("sush: read: -{}: option requires an argument", opt);
+                                    return Err(usage());
+                                },
+                            }
+                        },
+                        v => v.to_string(),
+                    };
+
+                    if let Err(msg) = set_option(opt, &value, ro) {
+                        eprintln!("sush: read: {}", msg);
+                        return Err(1);
+                    }
+                    break;
+                },
+                _ => {
+                    eprintln!("sush: read: -{}: invalid option", opt);
+                    return Err(usage());
+                },
+            }
+        }
+        pos += 1;
+    }
+    Ok(pos)
+}
+
+fn check_name(name: &String, core: &mut ShellCore) -> bool {
+    if ! utils::is_varname(name) {
+        eprintln!("sush: read: `{}': not a valid identifier", name);
+        return false;
+    }
+    if core.data.is_readonly(name) {
+        eprintln!("sush: read: {}: readonly variable", name);
         return false;
     }
+    true
+}
+
+fn wait_input(fd: RawFd, deadline: Option<Instant>) -> bool {
+    let deadline = match deadline {
+        Some(d) => d,
+        None    => return true,
+    };
 
-    let name_c = |c| ('a' <= c && c <= 'z') || ('A' <= c && c <= 'Z')
-                     || ('0' <= c && c <= '9') || '_' == c;
-    s.chars().position(|c| !name_c(c)) == None
+    let rest = deadline.saturating_duration_since(Instant::now());
+    let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    unsafe { libc::poll(&mut pollfd, 1, rest.as_millis() as libc::c_int) > 0 }
 }
 
-pub fn read(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
-        return 0;
+fn read_byte(fd: RawFd) -> Option<u8> {
+    let mut buf = [0; 1];
+    loop {
+        match unistd::read(fd, &mut buf) {
+            Ok(1) => return Some(buf[0]),
+            Err(nix::errno::Errno::EINTR) => continue,
+            _ => return None,
+        }
+    }
+}
+
+fn read_char(fd: RawFd, deadline: Option<Instant>) -> Result<Option<char>, ()> {
+    if ! wait_input(fd, deadline) {
+        return Err(());
     }
 
-    for a in &args[1..] {
-        if ! is_varname(&a) {
-            eprintln!("bash: read: `{}': not a valid identifier", &a);
-            return 1;
-        }else{
-            core.data.set_param(&a, "");
+    let first = match read_byte(fd) {
+        Some(b) => b,
+        None    => return Ok(None),
+    };
+    let len = match first {
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _           => 1,
+    };
+
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(fd) {
+            Some(b) => bytes.push(b),
+            None    => break,
         }
     }
 
-    let mut line = String::new();
-    let len = std::io::stdin()
-        .read_line(&mut line)
-        .expect("SUSHI INTERNAL ERROR: Failed to read line");
+    Ok(String::from_utf8_lossy(&bytes).chars().next())
+}
+
+fn read_input(ro: &ReadOption) -> (Vec<InputChar>, End) {
+    let deadline = ro.timeout.map(|t| Instant::now() + Duration::from_secs_f64(t));
+    let mut ans = vec![];
 
-    let mut pos = 1;
-    let mut overflow = String::new();
-    for w in line.trim_end().split(' ') {
-        if pos < args.len()-1 {
-            core.data.set_param(&args[pos], &w);
-            pos += 1;
-        }else{
-            if overflow.len() != 0 {
-                overflow += " ";
+    loop {
+        if ro.nchars.is_some_and(|n| ans.len() >= n) {
+            return (ans, End::Delim);
+        }
+
+        let c = match read_char(ro.fd, deadline) {
+            Ok(Some(c)) => c,
+            Ok(None)    => return (ans, End::Eof),
+            Err(_)      => return (ans, End::Timeout),
+        };
+
+        if c == '\\' && ! ro.raw {
+            match read_char(ro.fd, deadline) {
+                Ok(Some('\n')) => {},
+                Ok(Some(c))    => ans.push((c, true)),
+                Ok(None)       => return (ans, End::Eof),
+                Err(_)         => return (ans, End::Timeout),
             }
-            overflow += &w;
-            core.data.set_param(&args[pos], &overflow);
+            continue;
         }
+
+        if c == ro.delim && ! ro.exact {
+            return (ans, End::Delim);
+        }
+        ans.push((c, false));
+    }
+}
+
+fn set_terminal(ro: &ReadOption) -> Option<termios::Termios> {
+    if unistd::isatty(ro.fd) != Ok(true) || ( ! ro.silent && ro.nchars.is_none() ) {
+        return None;
+    }
+
+    let fd = unsafe { BorrowedFd::borrow_raw(ro.fd) };
+    let backup = termios::tcgetattr(fd).ok()?;
+    let mut term = backup.clone();
+    if ro.silent {
+        term.local_flags.remove(LocalFlags::ECHO);
+    }
+    if ro.nchars.is_some() {
+        term.local_flags.remove(LocalFlags::ICANON);
+        term.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+        term.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
     }
+    termios::tcsetattr(fd, SetArg::TCSADRAIN, &term).ok()?;
+    Some(backup)
+}
+
+fn restore_terminal(fd: RawFd, backup: Option<termios::Termios>) {
+    if let Some(term) = backup {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+        let _ = termios::tcsetattr(fd, SetArg::TCSADRAIN, &term);
+    }
+}
+
+fn is_blank(c: &InputChar, ifs: &str) -> bool {
+    ! c.1 && subword::is_ifs_blank(c.0, ifs)
+}
+
+fn is_delim(c: &InputChar, ifs: &str) -> bool {
+    ! c.1 && ifs.contains(c.0)
+}
+
+fn to_string(chars: &[InputChar]) -> String {
+    chars.iter().map(|c| c.0).collect()
+}
+
+fn trim_start<'a>(line: &'a [InputChar], ifs: &str) -> &'a [InputChar] {
+    let len = line.iter().take_while(|c| is_blank(c, ifs)).count();
+    &line[len..]
+}
+
+fn trim_end<'a>(line: &'a [InputChar], ifs: &str) -> &'a [InputChar] {
+    let len = line.iter().rev().take_while(|c| is_blank(c, ifs)).count();
+    &line[..line.len()-len]
+}
+
+fn eat_field<'a>(line: &'a [InputChar], ifs: &str) -> (String, &'a [InputChar]) {
+    let len = line.iter().take_while(|c| ! is_delim(c, ifs)).count();
+    let mut rest = trim_start(&line[len..], ifs);
+    if let Some(c) = rest.first() {
+        if is_delim(c, ifs) && ! is_blank(c, ifs) {
+            rest = trim_start(&rest[1..], ifs);
+        }
+    }
+    (to_string(&line[..len]), rest)
+}
+
+fn last_field(line: &[InputChar], ifs: &str) -> String {
+    let line = trim_end(line, ifs);
+    let (field, rest) = eat_field(line, ifs);
+    match rest.is_empty() && field.chars().count() < line.len() {
+        true  => field, //a single trailing delimiter is removed
+        false => to_string(line),
+    }
+}
+
+fn split_all(line: &[InputChar], ifs: &str) -> Vec<String> {
+    let mut ans = vec![];
+    let mut line = trim_start(line, ifs);
+    while ! line.is_empty() {
+        let (field, rest) = eat_field(line, ifs);
+        ans.push(field);
+        line = rest;
+    }
+    ans
+}
+
+fn assign(names: &[String], line: &[InputChar], ro: &ReadOption, core: &mut ShellCore) {
+    let ifs = core.get_ifs();
+
+    if let Some(array) = &ro.array {
+        core.data.set_array(array, &split_all(line, &ifs));
+        return;
+    }
+    if names.is_empty() {
+        core.data.set_param("REPLY", &to_string(line));
+        return;
+    }
+    if ro.exact {
+        core.data.set_param(&names[0], &to_string(line));
+        return;
+    }
+
+    let mut line = trim_start(line, &ifs);
+    for name in &names[..names.len()-1] {
+        let (field, rest) = eat_field(line, &ifs);
+        core.data.set_param(name, &field);
+        line = rest;
+    }
+    core.data.set_param(&names[names.len()-1], &last_field(line, &ifs));
+}
+
+pub fn read(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut ro = ReadOption::new();
+    let names = match parse_options(&args[1..], &mut ro) {
+        Ok(n)  => args[n+1..].to_vec(),
+        Err(e) => return e,
+    };
+
+    for name in names.iter().chain(ro.array.iter()) {
+        if ! check_name(name, core) {
+            return 1;
+        }
+    }
+
+    if ro.timeout == Some(0.0) {
+        return match wait_input(ro.fd, Some(Instant::now())) {
+            true  => 0,
+            false => 1,
+        };
+    }
+
+    if let Some(prompt) = &ro.prompt {
+        if unistd::isatty(ro.fd) == Ok(true) {
+            eprint!("{}", prompt);
+            let _ = std::io::stderr().flush();
+        }
+    }
+
+    let backup = set_terminal(&ro);
+    let (line, end) = read_input(&ro);
+    restore_terminal(ro.fd, backup);
+
+    assign(&names, &line, &ro, core);
 
-    match len == 0 {
-        true  => 1,
-        false => 0,
+    match end {
+        End::Delim   => 0,
+        End::Eof     => 1,
+        End::Timeout => 142,
     }
 }
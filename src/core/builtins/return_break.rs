@@ -40,6 +40,7 @@ pub fn break_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
                 core.break_counter += n - 1;
             }else{
                 eprintln!("sush: break: {}: loop count out of range", args[1]);
+                core.break_counter = core.loop_level;
                 return 1;
             }
         },
@@ -50,3 +51,33 @@ pub fn break_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     };
     0
 }
+
+pub fn continue_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if core.loop_level <= 0 {
+        eprintln!("sush: continue: only meaningful in a `for', `while', or `until' loop");
+        return 0;
+    }
+
+    if args.len() < 2 {
+        core.continue_counter = 1;
+        return 0;
+    }
+
+    match args[1].parse::<i32>() {
+        Ok(n)  => {
+            if n > 0 {
+                core.continue_counter = std::cmp::min(n, core.loop_level);
+            }else{
+                eprintln!("sush: continue: {}: loop count out of range", args[1]);
+                core.break_counter = core.loop_level;
+                return 1;
+            }
+        },
+        Err(_) => {
+            eprintln!("sush: continue: {}: numeric argument required", args[1]);
+            core.break_counter = core.loop_level;
+            return 128;
+        },
+    };
+    0
+}
@@ -0,0 +1,32 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::elements::expr::conditional::{ConditionalExpr, CondElem};
+
+pub fn test(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let name = args[0].clone();
+    let mut args = args[1..].to_vec();
+    if name == "[" {
+        if args.last().map(|s| s.as_str()) != Some("]") {
+            eprintln!("sush: [: missing `]'");
+            return 2;
+        }
+        args.pop();
+    }
+
+    let result = match ConditionalExpr::parse_args(&args) {
+        Ok(mut expr) => expr.eval(core),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(CondElem::Ans(true))  => 0,
+        Ok(CondElem::Ans(false)) => 1,
+        Err(msg) => {
+            eprintln!("sush: {}: {}", &name, &msg);
+            2
+        },
+        _ => 2,
+    }
+}
@@ -0,0 +1,92 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::core::trap;
+use nix::sys::signal::Signal;
+use super::utils;
+
+fn print_trap(name: &str, core: &mut ShellCore) {
+    if let Some(command) = core.traps.get(name) {
+        println!("trap -- {} {}", utils::single_quote(command), name);
+    }
+}
+
+fn print_traps(names: &[String], core: &mut ShellCore) -> i32 {
+    if names.is_empty() {
+        let mut all: Vec<String> = core.traps.keys().cloned().collect();
+        all.sort();
+        all.iter().for_each(|n| print_trap(n, core));
+        return 0;
+    }
+
+    let mut exit_status = 0;
+    for n in names {
+        match trap::to_signal_name(n) {
+            Some(name) => print_trap(&name, core),
+            None => {
+                eprintln!("sush: trap: {}: invalid signal specification", n);
+                exit_status = 1;
+            },
+        }
+    }
+    exit_status
+}
+
+pub fn print_signal_list() -> i32 {
+    let signals: Vec<String> = Signal::iterator()
+                               .map(|s| format!("{:2}) {}", s as i32, s.as_str()))
+                               .collect();
+
+    for line in signals.chunks(5) {
+        println!("{}", line.join("\t"));
+    }
+    0
+}
+
+fn set_traps(command: Option<&str>, specs: &[String], core: &mut ShellCore) -> i32 {
+    let mut exit_status = 0;
+    for spec in specs {
+        let name = match trap::to_signal_name(spec) {
+            Some(n) => n,
+            None => {
+                eprintln!("sush: trap: {}: invalid signal specification", spec);
+                exit_status = 1;
+                continue;
+            },
+        };
+
+        match command {
+            Some(c) => core.set_trap(&name, c),
+            None    => core.reset_trap(&name),
+        }
+    }
+    exit_status
+}
+
+pub fn trap(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut args = args[1..].to_vec();
+    match args.first().map(|s| s.as_str()) {
+        None       => return print_traps(&[], core),
+        Some("-l") => return print_signal_list(),
+        Some("-p") => return print_traps(&args[1..], core),
+        Some("--") => {args.remove(0);},
+        _ => {},
+    }
+
+    if args.is_empty() {
+        return print_traps(&[], core);
+    }
+
+    if args.len() == 1 {
+        return set_traps(None, &args, core);
+    }
+
+    match args[0].as_str() {
+        "-" => set_traps(None, &args[1..], core),
+        c if trap::to_signal_name(c).is_some() && c.parse::<i32>().is_ok() => {
+            set_traps(None, &args, core)
+        },
+        c => set_traps(Some(c), &args[1..], core),
+    }
+}
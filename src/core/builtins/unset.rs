@@ -2,35 +2,104 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
+use crate::elements::command::arithmetic::ArithmeticCommand;
+
+fn is_readonly(core: &mut ShellCore, name: &str) -> bool {
+    if core.data.is_readonly(name) {
+        eprintln!("sush: unset: {}: cannot unset: readonly variable", name);
+        return true;
+    }
+    false
+}
 
 fn unset_all(core: &mut ShellCore, name: &str) -> i32 {
+    if name.contains('[') {
+        return unset_var(core, name);
+    }
+
+    if is_readonly(core, name) {
+        return 1;
+    }
     core.data.unset(name);
     0
 }
 
 fn unset_var(core: &mut ShellCore, name: &str) -> i32 {
+    if let Some((name, sub)) = name.split_once('[') {
+        return match sub.strip_suffix(']') {
+            Some(sub) => unset_elem(core, name, sub),
+            None => {
+                eprintln!("sush: unset: `{}[{}': not a valid identifier", name, sub);
+                1
+            },
+        };
+    }
+
+    if is_readonly(core, name) {
+        return 1;
+    }
     core.data.unset_var(name);
     0
 }
 
+fn unset_elem(core: &mut ShellCore, name: &str, sub: &str) -> i32 {
+    if is_readonly(core, name) {
+        return 1;
+    }
+
+    if sub == "@" || sub == "*" {
+        core.data.unset_var(name);
+        return 0;
+    }
+
+    let pos = match core.data.is_assoc(name) {
+        true  => sub.to_string(),
+        false => match ArithmeticCommand::eval_text(sub, core) {
+            Some(n) => n,
+            None    => return 1,
+        },
+    };
+
+    match core.data.unset_array_elem(name, &pos) {
+        true  => 0,
+        false => {
+            eprintln!("sush: unset: [{}]: bad array subscript", sub);
+            1
+        },
+    }
+}
+
 fn unset_function(core: &mut ShellCore, name: &str) -> i32 {
     core.data.unset_function(name);
     0
 }
 
 pub fn unset(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    match args[1].as_ref() {
-        "-f" => {
-            if args.len() > 2 {
-                return unset_function(core, &args[2]);
-            }
-        },
-        "-v" => {
-            if args.len() > 2 {
-                return unset_var(core, &args[2]);
-            }
-        },
-        name => return unset_all(core, name),
+    let (mut pos, mut func, mut var) = (1, false, false);
+    while pos < args.len() && args[pos].starts_with("-") {
+        match args[pos].as_str() {
+            "-f" => func = true,
+            "-v" => var = true,
+            "--" => {
+                pos += 1;
+                break;
+            },
+            opt => {
+                eprintln!("sush: unset: {}: invalid option", opt);
+                eprintln!("unset: usage: unset [-f] [-v] [name ...]");
+                return 2;
+            },
+        }
+        pos += 1;
     }
-    0
+
+    let mut exit_status = 0;
+    for name in &args[pos..] {
+        exit_status |= match (func, var) {
+            (true, _)  => unset_function(core, name),
+            (_, true)  => unset_var(core, name),
+            _          => unset_all(core, name),
+        };
+    }
+    exit_status
 }
@@ -46,3 +46,13 @@ pub fn make_canonical_path(core: &mut ShellCore, path_str: &str) -> PathBuf {
     }
     canonical
 }
+
+pub fn is_varname(s: &str) -> bool {
+    ! s.is_empty()
+    && ! s.starts_with(|c: char| c.is_ascii_digit())
+    && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn single_quote(s: &str) -> String {
+    "'".to_string() + &s.replace("'", "'\\''") + "'"
+}
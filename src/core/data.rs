@@ -1,11 +1,12 @@
 //SPDXFileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDXLicense-Identifier: BSD-3-Clause
 
+use crate::error_message;
 use crate::elements::array::Array;
 use crate::elements::word::Word;
 use crate::elements::command::function_def::FunctionDefinition;
 use std::env;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -13,29 +14,40 @@ pub enum Value {
     Single(Word),
     EvaluatedSingle(String),
     Array(Array),
-    EvaluatedArray(Vec<String>),
+    EvaluatedArray(BTreeMap<usize, String>),
+    AssocArray(HashMap<String, String>),
 }
 
 #[derive(Debug)]
 pub struct Data {
     pub flags: String,
     parameters: Vec<HashMap<String, Value>>,
+    attributes: Vec<HashMap<String, String>>,
     pub position_parameters: Vec<Vec<String>>,
     pub aliases: HashMap<String, String>,
     pub functions: HashMap<String, FunctionDefinition>,
     pub alias_memo: Vec<(String, String)>,
+    pub hash_table: HashMap<String, (usize, String)>,
 }
 
 impl Data {
     pub fn new() -> Data {
-        Data {
-            flags: String::new(),
+        let mut data = Data {
+            flags: "h".to_string(),
             parameters: vec![HashMap::new()],
+            attributes: vec![HashMap::new()],
             position_parameters: vec![vec![]],
             aliases: HashMap::new(),
             functions: HashMap::new(),
             alias_memo: vec![],
+            hash_table: HashMap::new(),
+        };
+
+        for (k, v) in env::vars() {
+            data.parameters[0].insert(k.clone(), Value::EvaluatedSingle(v));
+            data.attributes[0].insert(k, "x".to_string());
         }
+        data
     }
 
     pub fn get_param(&mut self, key: &str) -> String {
@@ -50,6 +62,10 @@ impl Data {
             };
         }
 
+        if key == "#" {
+            return self.get_position_params().len().to_string();
+        }
+
         if let Some(n) = self.get_position_param_pos(key) {
             let layer = self.position_parameters.len();
             return self.position_parameters[layer-1][n].to_string();
@@ -58,32 +74,30 @@ impl Data {
         match self.get_value(key) {
             Some(Value::EvaluatedSingle(v)) => return v.to_string(),
             Some(Value::EvaluatedArray(a)) => {
-                match a.len() {
-                    0 => return "".to_string(),
-                    _ => return a[0].to_string(),
-                }
+                return a.get(&0).cloned().unwrap_or_default();
+            },
+            Some(Value::AssocArray(a)) => {
+                return a.get("0").unwrap_or(&"".to_string()).to_string();
             },
             _  => {},
         }
 
-        match env::var(key) {
-            Ok(v) => {
-                self.set_layer_param(key, &v, 0);
-                v
-            },
-            _ => "".to_string()
-        }
+        "".to_string()
     }
 
     pub fn get_array(&mut self, key: &str, pos: &str) -> String {
         match self.get_value(key) {
+            Some(Value::AssocArray(a)) => {
+                if pos == "@" || pos == "*" {
+                    return self.get_array_all(key).join(" ");
+                }
+                return a.get(pos).unwrap_or(&"".to_string()).to_string();
+            },
             Some(Value::EvaluatedArray(a)) => {
-                if pos == "@" {
-                    return a.join(" ");
+                if pos == "@" || pos == "*" {
+                    return a.into_values().collect::<Vec<String>>().join(" ");
                 } else if let Ok(n) = pos.parse::<usize>() {
-                    if n < a.len() {
-                        return a[n].clone();
-                    }
+                    return a.get(&n).cloned().unwrap_or_default();
                 }
             },
             Some(Value::EvaluatedSingle(v)) => {
@@ -99,6 +113,43 @@ impl Data {
     }
 
     pub fn get_value(&mut self, key: &str) -> Option<Value> {
+        let key = self.resolve_nameref(key);
+        self.get_raw_value(&key)
+    }
+
+    pub fn is_set(&mut self, key: &str) -> bool {
+        if key == "@" || key == "*" {
+            return ! self.get_position_params().is_empty();
+        }
+
+        if let Ok(n) = key.parse::<usize>() {
+            return n < self.get_position_params().len() + 1;
+        }
+
+        if key.len() == 1 && "$?#-!_".contains(key) {
+            return true;
+        }
+
+        ! matches!(self.get_value(key), None | Some(Value::None))
+    }
+
+    /* declared without a value (e.g. declare foo, local foo) */
+    pub fn declare_layer_param(&mut self, key: &str, layer: usize) {
+        if ! self.parameters[layer].contains_key(key) {
+            self.parameters[layer].insert(key.to_string(), Value::None);
+        }
+    }
+
+    pub fn is_set_elem(&mut self, key: &str, pos: &str) -> bool {
+        match self.get_value(key) {
+            Some(Value::AssocArray(a)) => a.contains_key(pos),
+            Some(Value::EvaluatedArray(a)) => pos.parse::<usize>().is_ok_and(|n| a.contains_key(&n)),
+            Some(Value::EvaluatedSingle(_)) => pos == "0",
+            _ => false,
+        }
+    }
+
+    pub fn get_raw_value(&self, key: &str) -> Option<Value> {
         let num = self.parameters.len();
         for layer in (0..num).rev()  {
             match self.parameters[layer].get(key) {
@@ -112,17 +163,122 @@ impl Data {
     pub fn get_array_len(&mut self, key: &str) -> usize {
         match self.get_value(key) {
             Some(Value::EvaluatedArray(a)) => a.len(),
+            Some(Value::AssocArray(a)) => a.len(),
+            _ => 0,
+        }
+    }
+
+    /* one past the largest index, which negative subscripts count back from */
+    pub fn get_array_end(&mut self, key: &str) -> usize {
+        match self.get_value(key) {
+            Some(Value::EvaluatedArray(a)) => a.keys().last().map_or(0, |n| n + 1),
+            Some(Value::EvaluatedSingle(_)) => 1,
             _ => 0,
         }
     }
 
     pub fn get_array_all(&mut self, key: &str) -> Vec<String> {
         match self.get_value(key) {
-            Some(Value::EvaluatedArray(a)) => a.clone(),
+            Some(Value::EvaluatedArray(a)) => a.into_values().collect(),
+            Some(Value::AssocArray(a)) => {
+                self.get_array_keys(key).iter().map(|k| a[k].clone()).collect()
+            },
+            _ => vec![],
+        }
+    }
+
+    pub fn get_array_keys(&mut self, key: &str) -> Vec<String> {
+        match self.get_value(key) {
+            Some(Value::EvaluatedArray(a)) => a.keys().map(|n| n.to_string()).collect(),
+            Some(Value::AssocArray(a)) => {
+                let mut keys: Vec<String> = a.keys().cloned().collect();
+                keys.sort();
+                keys
+            },
+            Some(Value::EvaluatedSingle(_)) => vec!["0".to_string()],
             _ => vec![],
         }
     }
 
+    pub fn is_assoc(&mut self, key: &str) -> bool {
+        matches!(self.get_value(key), Some(Value::AssocArray(_)))
+    }
+
+    fn resolve_nameref(&self, key: &str) -> String {
+        let mut key = key.to_string();
+        for _ in 0..8 {
+            if ! self.get_attributes(&key).contains('n') {
+                break;
+            }
+            match self.get_raw_value(&key) {
+                Some(Value::EvaluatedSingle(v)) if ! v.is_empty() => key = v,
+                _ => break,
+            }
+        }
+        key
+    }
+
+    pub fn get_attributes(&self, key: &str) -> String {
+        for layer in self.attributes.iter().rev() {
+            if let Some(a) = layer.get(key) {
+                return a.clone();
+            }
+        }
+        "".to_string()
+    }
+
+    pub fn set_layer_attribute(&mut self, key: &str, attr: char, layer: usize) {
+        let mut attrs = self.get_attributes(key);
+        if ! attrs.contains(attr) {
+            attrs.push(attr);
+        }
+        self.attributes[layer].insert(key.to_string(), attrs);
+    }
+
+    pub fn remove_layer_attribute(&mut self, key: &str, attr: char, layer: usize) {
+        let attrs = self.get_attributes(key).replace(attr, "");
+        self.attributes[layer].insert(key.to_string(), attrs);
+    }
+
+    pub fn is_readonly(&self, key: &str) -> bool {
+        let key = self.resolve_nameref(key);
+        self.get_attributes(&key).contains('r')
+    }
+
+    pub fn get_var_layer(&self, key: &str) -> usize {
+        let num = self.parameters.len();
+        for layer in (0..num).rev() {
+            if self.parameters[layer].contains_key(key)
+            || self.attributes[layer].contains_key(key) {
+                return layer;
+            }
+        }
+        0
+    }
+
+    pub fn get_exported(&mut self) -> Vec<(String, String)> {
+        let mut ans = vec![];
+        for key in self.get_keys() {
+            if self.get_attributes(&key).contains('x') {
+                if let Some(Value::EvaluatedSingle(v)) = self.get_raw_value(&key) {
+                    ans.push( (key, v) );
+                }
+            }
+        }
+        ans
+    }
+
+    fn convert_case(&self, key: &str, val: &str) -> String {
+        let attrs = self.get_attributes(key);
+        if attrs.contains('l') {
+            val.to_lowercase()
+        }else if attrs.contains('u') {
+            val.to_uppercase()
+        }else{
+            val.to_string()
+        }
+    }
+
     pub fn get_position_params(&self) -> Vec<String> {
         match self.position_parameters.last() {
             Some(v) => v[1..].to_vec(),
@@ -145,11 +301,15 @@ impl Data {
     }
 
     pub fn set_layer_param(&mut self, key: &str, val: &str, layer: usize) {
-        match env::var(key) {
-            Ok(_) => env::set_var(key, val),
-            _     => {},
+        let key = self.resolve_nameref(key);
+        if key == "PATH" {
+            self.hash_table.clear();
         }
+        let val = self.convert_case(&key, val);
+        self.parameters[layer].insert(key, Value::EvaluatedSingle(val));
+    }
 
+    pub fn set_layer_raw_param(&mut self, key: &str, val: &str, layer: usize) {
         self.parameters[layer].insert(key.to_string(), Value::EvaluatedSingle(val.to_string()));
     }
 
@@ -157,13 +317,81 @@ impl Data {
         self.set_layer_param(key, val, 0);
     }
 
+    /* for names assigned by commands such as for and select */
+    pub fn set_checked_param(&mut self, key: &str, val: &str) -> bool {
+        if self.is_readonly(key) {
+            eprintln!("sush: {}", error_message::readonly(key));
+            self.set_param("?", "1");
+            return false;
+        }
+        self.set_param(key, val);
+        true
+    }
+
     pub fn set_local_param(&mut self, key: &str, val: &str) {
         let layer = self.parameters.len();
         self.set_layer_param(key, val, layer-1);
     }
 
     pub fn set_layer_array(&mut self, key: &str, vals: &Vec<String>, layer: usize) {
-        self.parameters[layer].insert(key.to_string(), Value::EvaluatedArray(vals.to_vec()));
+        let vals = vals.iter().cloned().enumerate().collect();
+        self.set_layer_indexed(key, &vals, layer);
+    }
+
+    pub fn set_layer_indexed(&mut self, key: &str, vals: &BTreeMap<usize, String>, layer: usize) {
+        let key = self.resolve_nameref(key);
+        let vals = vals.iter().map(|(n, v)| (*n, self.convert_case(&key, v))).collect();
+        self.parameters[layer].insert(key, Value::EvaluatedArray(vals));
+    }
+
+    pub fn set_layer_assoc(&mut self, key: &str, vals: &HashMap<String, String>, layer: usize) {
+        let key = self.resolve_nameref(key);
+        let vals = vals.iter().map(|(k, v)| (k.clone(), self.convert_case(&key, v))).collect();
+        self.parameters[layer].insert(key, Value::AssocArray(vals));
+    }
+
+    pub fn set_layer_array_elem(&mut self, key: &str, pos: &str, val: &str, layer: usize) -> bool {
+        let key = self.resolve_nameref(key);
+        let val = self.convert_case(&key, val);
+
+        match self.get_raw_value(&key) {
+            Some(Value::AssocArray(mut a)) => {
+                a.insert(pos.to_string(), val);
+                self.parameters[layer].insert(key, Value::AssocArray(a));
+                true
+            },
+            Some(Value::EvaluatedArray(a)) => self.set_array_elem(&key, a, pos, val, layer),
+            Some(Value::EvaluatedSingle(v)) => {
+                self.set_array_elem(&key, BTreeMap::from([(0, v)]), pos, val, layer)
+            },
+            _ => self.set_array_elem(&key, BTreeMap::new(), pos, val, layer),
+        }
+    }
+
+    fn set_array_elem(&mut self, key: &str, mut array: BTreeMap<usize, String>,
+                      pos: &str, val: String, layer: usize) -> bool {
+        let n = match pos.parse::<usize>() {
+            Ok(n) => n,
+            _     => return false,
+        };
+
+        array.insert(n, val);
+        self.parameters[layer].insert(key.to_string(), Value::EvaluatedArray(array));
+        true
+    }
+
+    pub fn set_layer_value(&mut self, key: &str, value: &Value, layer: usize) {
+        if self.flags.contains('a') { //allexport
+            let key = self.resolve_nameref(key);
+            self.set_layer_attribute(&key, 'x', layer);
+        }
+
+        match value {
+            Value::EvaluatedSingle(v) => self.set_layer_param(key, v, layer),
+            Value::EvaluatedArray(a)  => self.set_layer_indexed(key, a, layer),
+            Value::AssocArray(a)      => self.set_layer_assoc(key, a, layer),
+            _ => {},
+        }
     }
 
     pub fn set_array(&mut self, key: &str, vals: &Vec<String>) {
@@ -177,10 +405,12 @@ impl Data {
 
     pub fn push_local(&mut self) {
         self.parameters.push(HashMap::new());
+        self.attributes.push(HashMap::new());
     }
 
     pub fn pop_local(&mut self) {
         self.parameters.pop();
+        self.attributes.pop();
     }
 
     pub fn get_layer_num(&mut self) -> usize {
@@ -211,10 +441,6 @@ impl Data {
     }
 
     fn replace_alias_core(&self, word: &mut String) -> bool {
-        if self.flags.find('i') == None {
-            return false;
-        }
-
         let mut ans = false;
         let mut prev_head = "".to_string();
 
@@ -240,6 +466,32 @@ impl Data {
         for layer in &mut self.parameters {
             layer.remove(key);
         }
+        for layer in &mut self.attributes {
+            layer.remove(key);
+        }
+    }
+
+    pub fn unset_array_elem(&mut self, key: &str, pos: &str) -> bool {
+        let key = self.resolve_nameref(key);
+        let layer = self.get_var_layer(&key);
+        match self.parameters[layer].get_mut(&key) {
+            Some(Value::AssocArray(a)) => {a.remove(pos);},
+            Some(Value::EvaluatedArray(a)) => {
+                let end = a.keys().last().map_or(0, |n| n + 1);
+                let n = match pos.parse::<isize>() {
+                    Ok(n) if n < 0 => n + end as isize,
+                    Ok(n) => n,
+                    _ => return false,
+                };
+                if n < 0 {
+                    return false;
+                }
+                a.remove(&(n as usize));
+            },
+            Some(Value::EvaluatedSingle(_)) if pos == "0" => {self.parameters[layer].remove(&key);},
+            _ => {},
+        }
+        true
     }
 
     pub fn unset_function(&mut self, key: &str) {
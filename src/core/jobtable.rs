@@ -10,11 +10,13 @@ use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 #[derive(Debug)]
 pub struct JobEntry {
     pub id: usize,
-    pids: Vec<Pid>,
+    pub pids: Vec<Pid>,
     proc_statuses: Vec<WaitStatus>,
-    display_status: String,
+    pub display_status: String,
     pub text: String,
     change: bool,
+    pub no_hup: bool, // disown -h
+    pub coproc: Option<String>, // the name of the array of a coprocess
 }
 
 fn wait_nonblock(pid: &Pid, status: &mut WaitStatus) {
@@ -67,8 +69,10 @@ impl JobEntry {
             pids: pids.into_iter().flatten().collect(),
             proc_statuses: statuses.to_vec(),
             display_status: status.to_string(),
-            text: text.to_string(),
+            text: text.trim().to_string(),
             change: false,
+            no_hup: false,
+            coproc: None,
         }
     }
 
@@ -106,14 +110,32 @@ impl JobEntry {
         exit_status
     }
 
-    pub fn print(&self, priority: &Vec<usize>) {
-        if priority[0] == self.id {
-            println!("[{}]+  {}     {}", self.id, &self.display_status, &self.text);
+    pub fn exit_status(&self) -> Option<i32> {
+        if self.proc_statuses.iter().any(still) {
+            return None;
+        }
+
+        match self.proc_statuses.last()? {
+            WaitStatus::Exited(_, es) => Some(*es),
+            WaitStatus::Signaled(_, sig, _) => Some(*sig as i32 + 128),
+            _ => None,
+        }
+    }
+
+    pub fn print(&self, priority: &Vec<usize>, with_pid: bool) {
+        let mark = if priority[0] == self.id {
+            "+"
         }else if priority.len() > 1 && priority[1] == self.id {
-            println!("[{}]-  {}     {}", self.id, &self.display_status, &self.text);
+            "-"
         }else {
-            println!("[{}]   {}     {}", self.id, &self.display_status, &self.text);
-        }
+            " "
+        };
+
+        let pid = match (with_pid, self.pids.first()) {
+            (true, Some(p)) => p.to_string() + " ",
+            _ => "".to_string(),
+        };
+        println!("[{}]{}  {}{}     {}", self.id, mark, pid, &self.display_status, &self.text);
     }
 
     fn display_status_on_signal(signal: &signal::Signal, coredump: bool) -> String {
@@ -163,8 +185,34 @@ impl JobEntry {
     }
 
     pub fn send_cont(&mut self) {
-        for pid in &self.pids {
-            let _ = signal::kill(Pid::from_raw(-1 * i32::from(*pid)), signal::SIGCONT);            
+        let _ = self.send_signal(Some(signal::SIGCONT));
+    }
+
+    /* for huponexit */
+    pub fn send_hup(&self) {
+        if self.no_hup {
+            return;
+        }
+
+        let _ = self.send_signal(Some(signal::SIGHUP));
+        if self.display_status == "Stopped" {
+            let _ = self.send_signal(Some(signal::SIGCONT));
+        }
+    }
+
+    /* to the process group, or to each process when the job shares
+       the group of the shell without job control */
+    pub fn send_signal(&self, sig: Option<signal::Signal>) -> nix::Result<()> {
+        let pgid = self.solve_pgid();
+        if pgid.as_raw() != 0 && pgid != unistd::getpgrp() {
+            return signal::kill(Pid::from_raw(-pgid.as_raw()), sig);
+        }
+
+        let results: Vec<nix::Result<()>> = self.pids.iter()
+            .map(|pid| signal::kill(*pid, sig)).collect();
+        match results.iter().any(|r| r.is_ok()) {
+            true  => Ok(()),
+            false => results.into_iter().next().unwrap_or(Err(nix::errno::Errno::ESRCH)),
         }
     }
 
@@ -184,12 +232,29 @@ impl ShellCore {
         for e in self.job_table.iter_mut() {
             e.update_status(false);
         }
+        self.jobtable_unset_coprocs();
+    }
+
+    /* the variables of a coprocess are removed after it finishes */
+    pub fn jobtable_unset_coprocs(&mut self) {
+        let finished: Vec<(String, String)> = self.job_table.iter()
+            .filter(|e| e.exit_status().is_some())
+            .filter_map(|e| Some((e.coproc.clone()?, e.pids[0].to_string())))
+            .collect();
+
+        for (name, pid) in finished {
+            let pid_name = name.clone() + "_PID";
+            if self.data.get_param(&pid_name) == pid {
+                self.data.unset_var(&name);
+                self.data.unset_var(&pid_name);
+            }
+        }
     }
 
     pub fn jobtable_print_status_change(&mut self) {
         for e in self.job_table.iter_mut() {
             if e.change {
-                e.print(&self.job_table_priority);
+                e.print(&self.job_table_priority, false);
                 e.change = false;
             }
         }
@@ -200,6 +265,35 @@ impl ShellCore {
         self.job_table_priority.retain(|id| ids.contains(id) );
     }
 
+    /* an interactive shell warns of stopped (and running with checkjobs)
+       jobs once before exit */
+    pub fn jobtable_defer_exit(&mut self) -> bool {
+        if self.exit_warned || ! self.data.flags.contains('i') || self.is_subshell {
+            return false;
+        }
+
+        self.jobtable_check_status();
+        let checkjobs = self.shopts.query("checkjobs");
+        let msg = if self.job_table.iter().any(|j| j.display_status == "Stopped") {
+            "There are stopped jobs."
+        }else if checkjobs && self.job_table.iter().any(|j| j.display_status == "Running") {
+            "There are running jobs."
+        }else{
+            return false;
+        };
+
+        if checkjobs {
+            self.job_table.iter().for_each(|e| e.print(&self.job_table_priority, false));
+        }
+        eprintln!("{}", msg);
+        self.exit_warned = true;
+        true
+    }
+
+    pub fn jobtable_send_hup(&mut self) {
+        self.job_table.iter().for_each(|e| e.send_hup());
+    }
+
     pub fn generate_new_job_id(&self) -> usize {
         match self.job_table.last() {
             None      => 1,
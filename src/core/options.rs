@@ -3,6 +3,14 @@
 
 use std::collections::HashMap;
 
+/* single-letter options of the set command and their long names */
+pub const FLAG_OPTIONS: [(char, &str); 13] = [
+    ('a', "allexport"), ('b', "notify"), ('e', "errexit"), ('f', "noglob"),
+    ('h', "hashall"), ('m', "monitor"), ('n', "noexec"), ('u', "nounset"),
+    ('v', "verbose"), ('x', "xtrace"), ('C', "noclobber"), ('E', "errtrace"),
+    ('T', "functrace"),
+];
+
 #[derive(Debug)]
 pub struct Options {
     opts: HashMap<String, bool>,
@@ -14,6 +22,7 @@ impl Options {
             opts: HashMap::new(),
         };
 
+        options.opts.insert("ignoreeof".to_string(), false);
         options.opts.insert("pipefail".to_string(), false);
 
         options
@@ -41,7 +50,22 @@ impl Options {
             options.opts.insert(opt.to_string(), false);
         }*/
 
+        options.opts.insert("autocd".to_string(), false);
+        options.opts.insert("cdspell".to_string(), false);
+        options.opts.insert("checkjobs".to_string(), false);
+        options.opts.insert("dirspell".to_string(), false);
+        options.opts.insert("dotglob".to_string(), false);
+        options.opts.insert("expand_aliases".to_string(), false);
         options.opts.insert("extglob".to_string(), true);
+        options.opts.insert("failglob".to_string(), false);
+        options.opts.insert("globstar".to_string(), false);
+        options.opts.insert("huponexit".to_string(), false);
+        options.opts.insert("inherit_errexit".to_string(), false);
+        options.opts.insert("lastpipe".to_string(), false);
+        options.opts.insert("nocaseglob".to_string(), false);
+        options.opts.insert("nocasematch".to_string(), false);
+        options.opts.insert("nullglob".to_string(), false);
+        options.opts.insert("xpg_echo".to_string(), false);
 
         options
     }
@@ -67,6 +91,14 @@ impl Options {
         format!("set {}o {}", onoff_str, opt)
     }
 
+    pub fn flag_of(name: &str) -> Option<char> {
+        FLAG_OPTIONS.iter().find(|f| f.1 == name).map(|f| f.0)
+    }
+
+    pub fn name_of(flag: char) -> Option<&'static str> {
+        FLAG_OPTIONS.iter().find(|f| f.0 == flag).map(|f| f.1)
+    }
+
     pub fn print_opt(&self, opt: &str) -> bool {
         match self.opts.get_key_value(opt) {
             None     => {
@@ -89,13 +121,12 @@ impl Options {
         list.iter().for_each(|e| println!("{}", e));
     }
 
-    pub fn print_all2(&self) {
-        let mut list = self.opts.iter()
-                       .map(|opt| Self::format2(opt.0, *opt.1))
-                       .collect::<Vec<String>>();
+    pub fn get_all(&self) -> Vec<(String, bool)> {
+        self.opts.iter().map(|opt| (opt.0.clone(), *opt.1)).collect()
+    }
 
-        list.sort();
-        list.iter().for_each(|e| println!("{}", e));
+    pub fn exist(&self, opt: &str) -> bool {
+        self.opts.contains_key(opt)
     }
 
     pub fn print_if(&self, onoff: bool) {
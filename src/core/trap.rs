@@ -0,0 +1,127 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{Feeder, Script, ShellCore};
+use nix::sys::signal;
+use nix::sys::signal::{Signal, SigHandler};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+
+pub const PSEUDO_SIGNALS: [&str; 4] = ["EXIT", "ERR", "DEBUG", "RETURN"];
+
+pub fn to_signal_name(spec: &str) -> Option<String> {
+    if let Ok(n) = spec.parse::<i32>() {
+        return match n {
+            0 => Some("EXIT".to_string()),
+            _ => Signal::try_from(n).ok().map(|s| s.as_str().to_string()),
+        };
+    }
+
+    let upper = spec.to_uppercase();
+    if PSEUDO_SIGNALS.contains(&upper.as_str()) {
+        return Some(upper);
+    }
+
+    let name = match upper.starts_with("SIG") {
+        true  => upper,
+        false => "SIG".to_string() + &upper,
+    };
+    Signal::from_str(&name).ok().map(|s| s.as_str().to_string())
+}
+
+fn set_handler(sig: Signal, handler: SigHandler) {
+    let _ = unsafe { signal::signal(sig, handler) };
+}
+
+impl ShellCore {
+    pub fn set_trap(&mut self, name: &str, command: &str) {
+        self.remove_signal_flag(name);
+
+        if let Ok(sig) = Signal::from_str(name) {
+            if command.is_empty() {
+                set_handler(sig, SigHandler::SigIgn);
+            }else{
+                let flag = Arc::new(AtomicBool::new(false));
+                if let Ok(id) = signal_hook::flag::register(sig as i32, Arc::clone(&flag)) {
+                    self.trap_flags.insert(name.to_string(), (flag, id));
+                }
+            }
+        }
+
+        self.traps.insert(name.to_string(), command.to_string());
+    }
+
+    pub fn reset_trap(&mut self, name: &str) {
+        self.remove_signal_flag(name);
+        if let Ok(sig) = Signal::from_str(name) {
+            set_handler(sig, SigHandler::SigDfl);
+        }
+        self.traps.remove(name);
+    }
+
+    fn remove_signal_flag(&mut self, name: &str) {
+        if let Some((_, id)) = self.trap_flags.remove(name) {
+            signal_hook::low_level::unregister(id);
+        }
+    }
+
+    pub fn reset_traps_in_subshell(&mut self) {
+        let names: Vec<String> = self.traps.iter()
+                                     .filter(|(_, c)| ! c.is_empty())
+                                     .map(|(n, _)| n.clone())
+                                     .collect();
+        for name in names {
+            self.reset_trap(&name);
+        }
+    }
+
+    pub fn run_trap(&mut self, name: &str) {
+        if self.trap_running {
+            return;
+        }
+
+        let command = match self.traps.get(name) {
+            Some(c) if ! c.is_empty() => c.clone(),
+            _ => return,
+        };
+
+        let exit_status = self.data.get_param("?");
+        self.trap_running = true;
+        let mut feeder = Feeder::new(&command);
+        if let Some(mut s) = Script::parse(&mut feeder, self, false) {
+            s.exec(self);
+        }
+        self.trap_running = false;
+        self.data.set_param("?", &exit_status);
+    }
+
+    pub fn check_trapped_signals(&mut self) {
+        let caught: Vec<String> = self.trap_flags.iter()
+                                      .filter(|(_, (flag, _))| flag.swap(false, Relaxed))
+                                      .map(|(name, _)| name.clone())
+                                      .collect();
+        for name in caught {
+            self.run_trap(&name);
+        }
+    }
+
+    pub fn run_err_trap(&mut self) {
+        if self.data.get_param("?") != "0"
+        && ! self.errexit_ignored
+        && ! self.errexit_exempted
+        && ( self.source_function_level == 0 || self.data.flags.contains('E') ) {
+            self.run_trap("ERR");
+        }
+    }
+
+    pub fn run_debug_trap(&mut self, command: &str) {
+        if self.trap_running
+        || ( self.source_function_level > 0 && ! self.data.flags.contains('T') ) {
+            return;
+        }
+        self.data.set_param("BASH_COMMAND", command);
+        self.run_trap("DEBUG");
+    }
+}
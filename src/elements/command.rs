@@ -3,10 +3,12 @@
 
 pub mod arithmetic;
 pub mod case;
+pub mod coproc;
 pub mod simple;
 pub mod paren;
 pub mod brace;
 pub mod r#for;
+pub mod select;
 pub mod test;
 pub mod function_def;
 pub mod r#while;
@@ -15,12 +17,14 @@ pub mod r#if;
 use crate::{ShellCore, Feeder, Script};
 use self::arithmetic::ArithmeticCommand;
 use self::case::CaseCommand;
+use self::coproc::CoprocCommand;
 use self::simple::SimpleCommand;
 use self::paren::ParenCommand;
 use self::brace::BraceCommand;
 use self::function_def::FunctionDefinition;
 use self::r#while::WhileCommand;
 use self::r#for::ForCommand;
+use self::select::SelectCommand;
 use self::r#if::IfCommand;
 use self::test::TestCommand;
 use std::fmt;
@@ -140,7 +144,9 @@ pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Box<dyn Comman
     else if let Some(a) = BraceCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = ForCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = WhileCommand::parse(feeder, core) { Some(Box::new(a)) }
+    else if let Some(a) = SelectCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = CaseCommand::parse(feeder, core) { Some(Box::new(a)) }
     else if let Some(a) = TestCommand::parse(feeder, core) { Some(Box::new(a)) }
+    else if let Some(a) = CoprocCommand::parse(feeder, core) { Some(Box::new(a)) }
     else{ None }
 }
@@ -51,6 +51,14 @@ impl ArithmeticCommand {
         Some(ans)
     }
 
+    pub fn eval_text(text: &str, core: &mut ShellCore) -> Option<String> {
+        let mut feeder = Feeder::new(&("((".to_owned() + text + "))"));
+        match Self::parse(&mut feeder, core) {
+            Some(mut a) => a.eval(core),
+            None        => None,
+        }
+    }
+
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
         if ! feeder.starts_with("((") {
             return None;
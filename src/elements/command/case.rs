@@ -33,6 +33,7 @@ impl Command for CaseCommand {
         };
 
         let extglob = core.shopts.query("extglob");
+        let nocase = core.shopts.query("nocasematch");
 
         for e in &mut self.patterns_script_end {
             for pattern in &mut e.0 {
@@ -41,7 +42,12 @@ impl Command for CaseCommand {
                     _       => continue,
                 };
 
-                if glob::compare(&w, &p, extglob) || next {
+                let matched = match nocase {
+                    true  => glob::compare_nocase(&w, &p, extglob),
+                    false => glob::compare(&w, &p, extglob),
+                };
+
+                if matched || next {
                     e.1.exec(core);
 
                     if e.2 == ";;" {
@@ -0,0 +1,192 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::core::jobtable::JobEntry;
+use super::{Command, Pipe, Redirect};
+use crate::elements::command;
+use crate::elements::io;
+use super::arithmetic::ArithmeticCommand;
+use super::brace::BraceCommand;
+use super::case::CaseCommand;
+use super::paren::ParenCommand;
+use super::r#for::ForCommand;
+use super::r#if::IfCommand;
+use super::r#while::WhileCommand;
+use super::select::SelectCommand;
+use super::test::TestCommand;
+use nix::{fcntl, unistd};
+use nix::sys::wait::WaitStatus;
+use nix::unistd::Pid;
+use std::os::fd::{IntoRawFd, RawFd};
+
+#[derive(Debug, Clone)]
+pub struct CoprocCommand {
+    text: String,
+    name: String,
+    command: Option<Box<dyn Command>>,
+    redirects: Vec<Redirect>,
+    force_fork: bool,
+}
+
+impl Command for CoprocCommand {
+    fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Option<Pid> {
+        if self.force_fork || pipe.is_connected() {
+            return self.fork_exec(core, pipe);
+        }
+
+        self.run(core, false);
+        None
+    }
+
+    fn run(&mut self, core: &mut ShellCore, fork: bool) {
+        let pgid = match core.is_subshell {
+            true  => unistd::getpgrp(),
+            false => Pid::from_raw(0),
+        };
+
+        let backup = core.tty_fd.as_ref().map(|fd| fd.try_clone().unwrap());
+        core.tty_fd = None;
+        let status = match self.start(core, pgid) {
+            true  => "0",
+            false => "1",
+        };
+        core.tty_fd = backup;
+
+        core.data.set_param("?", status);
+        if fork {
+            core.exit();
+        }
+    }
+
+    fn get_text(&self) -> String { self.text.clone() }
+    fn get_redirects(&mut self) -> &mut Vec<Redirect> { &mut self.redirects }
+    fn set_force_fork(&mut self) { self.force_fork = true; }
+    fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
+    fn force_fork(&self) -> bool { self.force_fork }
+}
+
+fn to_high_fd(fd: RawFd) -> RawFd {
+    match fcntl::fcntl(fd, fcntl::F_DUPFD_CLOEXEC(60)) {
+        Ok(newfd) => {
+            io::close(fd, "Cannot close coproc pipe");
+            newfd
+        },
+        Err(_) => fd,
+    }
+}
+
+impl CoprocCommand {
+    fn start(&mut self, core: &mut ShellCore, pgid: Pid) -> bool {
+        if core.data.is_readonly(&self.name) {
+            eprintln!("sush: {}: readonly variable", &self.name);
+            return false;
+        }
+
+        let (in_recv, in_send) = unistd::pipe().expect("Cannot open pipe");
+        let (out_recv, out_send) = unistd::pipe().expect("Cannot open pipe");
+        let out_recv = to_high_fd(out_recv.into_raw_fd());
+
+        /* the write end of the input is closed in the child as recv */
+        let mut pipe = Pipe::new(String::new());
+        pipe.recv = in_send.into_raw_fd();
+        pipe.send = out_send.into_raw_fd();
+        pipe.prev = in_recv.into_raw_fd();
+        pipe.pgid = pgid;
+
+        let mut command = self.command.clone().unwrap();
+        let pid = match command.exec(core, &mut pipe) {
+            Some(pid) => pid,
+            None => {
+                pipe.parent_close();
+                io::close(pipe.recv, "Cannot close coproc pipe");
+                io::close(out_recv, "Cannot close coproc pipe");
+                return false;
+            },
+        };
+
+        let fds = vec![out_recv.to_string(), to_high_fd(pipe.recv).to_string()];
+        core.data.set_array(&self.name, &fds);
+        core.data.set_param(&(self.name.clone() + "_PID"), &pid.to_string());
+
+        let new_job_id = core.generate_new_job_id();
+        core.job_table_priority.insert(0, new_job_id);
+        let mut entry = JobEntry::new(vec![Some(pid)], &vec![ WaitStatus::StillAlive ],
+                                      &self.text, "Running", new_job_id);
+        entry.coproc = Some(self.name.clone());
+        core.job_table.push(entry);
+        true
+    }
+
+    fn new() -> CoprocCommand {
+        CoprocCommand {
+            text: String::new(),
+            name: "COPROC".to_string(),
+            command: None,
+            redirects: vec![],
+            force_fork: false,
+        }
+    }
+
+    fn eat_compound_command(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        ans.command = if let Some(a) = IfCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = ArithmeticCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = ParenCommand::parse(feeder, core, false) { Some(Box::new(a)) }
+        else if let Some(a) = BraceCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = ForCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = WhileCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = SelectCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = CaseCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = TestCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else {None};
+
+        ans.command.is_some()
+    }
+
+    /* a name is given only when a compound command follows it */
+    fn eat_name_and_compound_command(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return false;
+        }
+
+        feeder.set_backup();
+        let mut text = feeder.consume(len);
+        let name = text.clone();
+        command::eat_blank_with_comment(feeder, core, &mut text);
+
+        if Self::eat_compound_command(feeder, ans, core) {
+            feeder.pop_backup();
+            ans.name = name;
+            ans.text += &text;
+            true
+        }else{
+            feeder.rewind();
+            false
+        }
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with("coproc") {
+            return None;
+        }
+        let mut ans = Self::new();
+        ans.text = feeder.consume(6);
+
+        if ! command::eat_blank_with_comment(feeder, core, &mut ans.text) {
+            return None;
+        }
+
+        if ! Self::eat_name_and_compound_command(feeder, &mut ans, core) {
+            ans.command = command::parse(feeder, core);
+        }
+
+        match &ans.command {
+            Some(c) => {
+                ans.text += &c.get_text();
+                Some(ans)
+            },
+            None => None,
+        }
+    }
+}
@@ -34,10 +34,7 @@ impl Command for ForCommand {
             core.data.set_param("?", "1");
         }
 
-        core.loop_level -= 1;
-        if core.loop_level == 0 {
-            core.break_counter = 0;
-        }
+        core.leave_loop();
     }
 
     fn get_text(&self) -> String { self.text.clone() }
@@ -74,14 +71,15 @@ impl ForCommand {
                 return false;
             }
 
-            core.data.set_param(&self.name, &p);
+            if ! core.data.set_checked_param(&self.name, &p) {
+                return false;
+            }
 
             self.do_script.as_mut()
                 .expect(&error_message::internal_str("no script)"))
                 .exec(core);
 
-            if core.break_counter > 0 {
-                core.break_counter -= 1;
+            if core.check_loop_exit() {
                 break;
             }
         }
@@ -119,8 +117,7 @@ impl ForCommand {
                 .expect(&error_message::internal_str("no script"))
                 .exec(core);
 
-            if core.break_counter > 0 {
-                core.break_counter -= 1;
+            if core.check_loop_exit() {
                 break;
             }
 
@@ -4,12 +4,12 @@
 use crate::{error_message, ShellCore, Feeder};
 use super::{Command, Pipe, Redirect};
 use crate::elements::command;
-use crate::elements::command::{BraceCommand, IfCommand, ParenCommand, WhileCommand};
+use crate::elements::command::{BraceCommand, IfCommand, ParenCommand, SelectCommand, WhileCommand};
 use nix::unistd::Pid;
 
 fn reserved(w: &str) -> bool {
     match w {
-        "{" | "}" | "while" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" => true,
+        "{" | "}" | "while" | "until" | "select" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" => true,
         _ => false,
     }
 }
@@ -61,12 +61,20 @@ impl FunctionDefinition {
 
         let mut dummy = Pipe::new("|".to_string());
 
+        let return_trap = core.traps.get("RETURN").cloned();
+        let loop_level = core.loop_level;
+        core.loop_level = 0;
         core.source_function_level += 1;
         let pid = self.command.clone()
                         .expect(&error_message::internal_str("empty function"))
                         .exec(core, &mut dummy);
         core.return_flag = false;
+        core.errexit_exempted = false;
+        if core.data.flags.contains('T') || core.traps.get("RETURN") != return_trap.as_ref() {
+            core.run_trap("RETURN");
+        }
         core.source_function_level -= 1;
+        core.loop_level = loop_level;
 
         core.data.position_parameters.pop();
 
@@ -91,6 +99,7 @@ impl FunctionDefinition {
         else if let Some(a) = ParenCommand::parse(feeder, core, false) { Some(Box::new(a)) }
         else if let Some(a) = BraceCommand::parse(feeder, core) { Some(Box::new(a)) }
         else if let Some(a) = WhileCommand::parse(feeder, core) { Some(Box::new(a)) }
+        else if let Some(a) = SelectCommand::parse(feeder, core) { Some(Box::new(a)) }
         else {None};
 
         if let Some(c) = &ans.command {
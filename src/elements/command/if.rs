@@ -18,7 +18,8 @@ pub struct IfCommand {
 impl Command for IfCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) {
         for i in 0..self.if_elif_scripts.len() {
-            self.if_elif_scripts[i].exec(core);
+            core.exec_ignoring_errexit(|c| self.if_elif_scripts[i].exec(c));
+
             if core.data.get_param("?") == "0" {
                 self.then_scripts[i].exec(core);
                 return;
@@ -27,7 +28,7 @@ impl Command for IfCommand {
 
         match self.else_script.as_mut() {
             Some(s) => s.exec(core),
-            _ => {},
+            _ => core.data.set_param("?", "0"),
         }
     }
 
@@ -0,0 +1,260 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error_message, ShellCore, Feeder, Script};
+use crate::core::builtins::read;
+use super::{Command, Redirect};
+use crate::elements::command;
+use crate::elements::word::Word;
+use std::io::Write;
+use std::sync::atomic::Ordering::Relaxed;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone)]
+pub struct SelectCommand {
+    text: String,
+    name: String,
+    has_in: bool,
+    values: Vec<Word>,
+    do_script: Option<Script>,
+    redirects: Vec<Redirect>,
+    force_fork: bool,
+}
+
+impl Command for SelectCommand {
+    fn run(&mut self, core: &mut ShellCore, _: bool) {
+        core.loop_level += 1;
+
+        if ! self.run_menu(core) && core.data.get_param("?") == "0" {
+            core.data.set_param("?", "1");
+        }
+
+        core.leave_loop();
+    }
+
+    fn get_text(&self) -> String { self.text.clone() }
+    fn get_redirects(&mut self) -> &mut Vec<Redirect> { &mut self.redirects }
+    fn set_force_fork(&mut self) { self.force_fork = true; }
+    fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
+    fn force_fork(&self) -> bool { self.force_fork }
+}
+
+fn number_len(n: usize) -> usize {
+    n.to_string().len()
+}
+
+fn indent(mut from: usize, to: usize, out: &mut String) {
+    while from < to {
+        if to / 8 > from / 8 {
+            out.push('\t');
+            from += 8 - from % 8;
+        }else{
+            out.push(' ');
+            from += 1;
+        }
+    }
+}
+
+/* the same layout as the one of bash */
+fn print_menu(values: &[String], core: &mut ShellCore) {
+    let columns = core.data.get_param("COLUMNS").parse::<usize>().unwrap_or(80);
+    let indices_len = number_len(values.len());
+    let max_elem_len = values.iter().map(|v| v.width()).max().unwrap_or(0) + indices_len + 4;
+
+    let cols = std::cmp::max(columns / max_elem_len, 1);
+    let mut rows = values.len().div_ceil(cols);
+    if rows == 1 {
+        rows = values.len();
+    }
+
+    let mut out = String::new();
+    for row in 0..rows {
+        let mut ind = row;
+        let mut pos = 0;
+        loop {
+            let len = if pos == 0 { number_len(rows) }else{ indices_len };
+            out += &format!("{:>len$}) {}", ind + 1, values[ind], len = len);
+            let elem_len = values[ind].width() + len + 2;
+
+            ind += rows;
+            if ind >= values.len() {
+                break;
+            }
+            indent(pos + elem_len, pos + max_elem_len, &mut out);
+            pos += max_elem_len;
+        }
+        out.push('\n');
+    }
+    eprint!("{}", out);
+}
+
+impl SelectCommand {
+    fn eval_values(&mut self, core: &mut ShellCore) -> Option<Vec<String>> {
+        let mut ans = vec![];
+        for w in &mut self.values {
+            match w.eval(core) {
+                Some(mut ws) => ans.append(&mut ws),
+                None     => return None,
+            }
+        }
+
+        Some(ans)
+    }
+
+    fn query(values: &[String], core: &mut ShellCore) -> Option<String> {
+        loop {
+            let ps3 = match core.data.get_value("PS3") {
+                Some(_) => core.data.get_param("PS3"),
+                None    => "#? ".to_string(),
+            };
+            eprint!("{}", ps3);
+            let _ = std::io::stderr().flush();
+
+            if read::read(core, &mut vec!["read".to_string()]) != 0 {
+                println!();
+                return None;
+            }
+
+            let reply = core.data.get_param("REPLY");
+            if reply.is_empty() {
+                print_menu(values, core);
+                continue;
+            }
+
+            return match reply.trim().parse::<usize>() {
+                Ok(n) if n >= 1 && n <= values.len() => Some(values[n-1].clone()),
+                _ => Some(String::new()),
+            };
+        }
+    }
+
+    fn run_menu(&mut self, core: &mut ShellCore) -> bool {
+        let values = match self.has_in {
+            true  => match self.eval_values(core) {
+                Some(vs) => vs,
+                None     => return false,
+            },
+            false => core.data.get_position_params(),
+        };
+
+        if values.is_empty() {
+            return true;
+        }
+
+        print_menu(&values, core);
+        loop {
+            if core.sigint.load(Relaxed) {
+                return false;
+            }
+
+            let selection = match Self::query(&values, core) {
+                Some(s) => s,
+                None    => {
+                    core.data.set_param("?", "1");
+                    return true;
+                },
+            };
+            if ! core.data.set_checked_param(&self.name, &selection) {
+                return false;
+            }
+
+            self.do_script.as_mut()
+                .expect(&error_message::internal_str("no script"))
+                .exec(core);
+
+            if core.check_loop_exit() {
+                break;
+            }
+        }
+        true
+    }
+
+    fn new() -> SelectCommand {
+        SelectCommand {
+            text: String::new(),
+            name: String::new(),
+            has_in: false,
+            values: vec![],
+            do_script: None,
+            redirects: vec![],
+            force_fork: false,
+        }
+    }
+
+    fn eat_name(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return false;
+        }
+
+        ans.name = feeder.consume(len);
+        ans.text += &ans.name.clone();
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+        true
+    }
+
+    fn eat_in_part(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) {
+        if ! feeder.starts_with("in") {
+            return;
+        }
+
+        ans.text += &feeder.consume(2);
+        ans.has_in = true;
+
+        loop {
+            command::eat_blank_with_comment(feeder, core, &mut ans.text);
+            match Word::parse(feeder, core, false) {
+                Some(w) => {
+                    ans.text += &w.text.clone();
+                    ans.values.push(w);
+                },
+                None    => return,
+            }
+        }
+    }
+
+    fn eat_end(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+        if feeder.starts_with(";") || feeder.starts_with("\n") {
+            ans.text += &feeder.consume(1);
+            command::eat_blank_with_comment(feeder, core, &mut ans.text);
+            true
+        }else{
+            false
+        }
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with("select") {
+            return None;
+        }
+        let mut ans = Self::new();
+        ans.text = feeder.consume(6);
+
+        if ! Self::eat_name(feeder, &mut ans, core) {
+            return None;
+        }
+        Self::eat_in_part(feeder, &mut ans, core);
+
+        if ! Self::eat_end(feeder, &mut ans, core) {
+            return None;
+        }
+
+        if feeder.len() == 0 && ! feeder.feed_additional_line(core) {
+            return None;
+        }
+
+        if command::eat_inner_script(feeder, core, "do", vec!["done"],  &mut ans.do_script, false) {
+            ans.text.push_str("do");
+            ans.text.push_str(&ans.do_script.as_mut().unwrap().get_text());
+            ans.text.push_str(&feeder.consume(4)); //done
+
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            Some(ans)
+        }else{
+            None
+        }
+    }
+}
@@ -8,6 +8,7 @@ use super::{Command, Pipe, Redirect};
 use crate::core::data::Value;
 use crate::elements::substitution::Substitution;
 use crate::elements::word::Word;
+use crate::utils::file_check;
 use nix::unistd;
 use std::ffi::CString;
 use std::{env, process};
@@ -20,7 +21,7 @@ use nix::errno::Errno;
 pub struct SimpleCommand {
     text: String,
     substitutions: Vec<Substitution>,
-    evaluated_subs: Vec<(String, Value)>,
+    evaluated_subs: Vec<(String, Option<String>, Value)>,
     words: Vec<Word>,
     args: Vec<String>,
     redirects: Vec<Redirect>,
@@ -32,12 +33,12 @@ pub struct SimpleCommand {
 
 impl Command for SimpleCommand {
     fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Option<Pid> {
-        if core.return_flag || core.break_counter > 0 {
-            return None;
-        }
-
         if ! self.eval_substitutions(core){
             core.data.set_param("?", "1");
+            if self.words.is_empty() && ! core.data.flags.contains('i')
+            && self.substitutions.iter().any(|s| core.data.is_readonly(&s.key)) {
+                core.exit();
+            }
             return None;
         }
 
@@ -91,10 +92,19 @@ impl Command for SimpleCommand {
 
 impl SimpleCommand {
     fn exec_external_command(&mut self, core: &mut ShellCore) -> ! {
-        self.set_environment_variables();
+        self.set_environment_variables(core);
         let cargs = Self::to_cargs(&self.args);
 
-        match unistd::execvp(&cargs[0], &cargs) {
+        let hashed = match core.data.flags.contains('h') {
+            true  => core.data.hash_table.get(&self.args[0]),
+            false => None,
+        };
+        let result = match hashed {
+            Some((_, path)) => unistd::execv(&CString::new(path.clone()).unwrap(), &cargs),
+            None            => unistd::execvp(&cargs[0], &cargs),
+        };
+
+        match result {
             Err(Errno::E2BIG) => {
                 eprintln!("sush: {}: Arg list too long", &self.args[0]);
                 process::exit(126)
@@ -117,10 +127,18 @@ impl SimpleCommand {
     }
 
     fn exec_command(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Option<Pid> {
+        self.set_autocd(core);
+        if self.args.len() == 1 && self.args[0] == "exec"
+        && ! core.data.functions.contains_key("exec") && ! pipe.is_connected() {
+            self.exec_redirects_persistently(core);
+            return None;
+        }
+
         if self.force_fork 
         || pipe.is_connected() 
         || ( ! core.builtins.contains_key(&self.args[0]) 
            && ! core.data.functions.contains_key(&self.args[0]) ) {
+            self.hash_command(core);
             self.fork_exec(core, pipe)
         }else{
             self.nofork_exec(core);
@@ -128,6 +146,51 @@ impl SimpleCommand {
         }
     }
 
+    /* remember the path of an external command (hashall) */
+    fn hash_command(&self, core: &mut ShellCore) {
+        let name = &self.args[0];
+        if ! core.data.flags.contains('h') || name.contains('/')
+        || core.builtins.contains_key(name) || core.data.functions.contains_key(name) {
+            return;
+        }
+
+        if let Some((hits, path)) = core.data.hash_table.get_mut(name) {
+            if file_check::is_executable(path) {
+                *hits += 1;
+                return;
+            }
+        }
+
+        let path = core.data.get_param("PATH");
+        match file_check::search_command(name, &path) {
+            Some(p) => {core.data.hash_table.insert(name.clone(), (1, p));},
+            None    => {core.data.hash_table.remove(name);},
+        }
+    }
+
+    /* a directory name given as a command is the argument of cd */
+    fn set_autocd(&mut self, core: &mut ShellCore) {
+        if self.args.len() != 1
+        || ! core.data.flags.contains('i')
+        || ! core.shopts.query("autocd")
+        || core.builtins.contains_key(&self.args[0])
+        || core.data.functions.contains_key(&self.args[0])
+        || ! file_check::is_dir(&self.args[0]) {
+            return;
+        }
+
+        eprintln!("cd -- {}", &self.args[0]);
+        self.args.insert(0, "cd".to_string());
+    }
+
+    /* exec only with redirections does not restore them */
+    fn exec_redirects_persistently(&mut self, core: &mut ShellCore) {
+        match self.redirects.iter_mut().all(|r| r.connect(false, core)) {
+            true  => core.data.set_param("?", "0"),
+            false => core.data.set_param("?", "1"),
+        }
+    }
+
     fn check_sigint(core: &mut ShellCore) -> bool {
         if core.sigint.load(Relaxed) {
             core.data.set_param("?", "130");
@@ -136,30 +199,42 @@ impl SimpleCommand {
         false
     }
 
-    fn exec_set_params(&mut self, core: &mut ShellCore) -> Option<Pid> {
+    /* without a layer, each variable is set where it is declared (e.g. by local) */
+    fn set_params(&mut self, core: &mut ShellCore, layer: Option<usize>) {
         for s in &self.evaluated_subs {
-            match &s.1 {
-                Value::EvaluatedSingle(v) => core.data.set_param(&s.0, &v),
-                Value::EvaluatedArray(a) => core.data.set_array(&s.0, &a),
-                _ => {},
+            let layer = layer.unwrap_or_else(|| core.data.get_var_layer(&s.0));
+            match (&s.1, &s.2) {
+                (Some(pos), Value::EvaluatedSingle(v)) => {
+                    core.data.set_layer_array_elem(&s.0, pos, v, layer);
+                },
+                (_, v) => core.data.set_layer_value(&s.0, v, layer),
             }
         }
-        None
     }
 
-    fn set_local_params(&mut self, core: &mut ShellCore) {
-        for s in &self.evaluated_subs {
-            match &s.1 {
-                Value::EvaluatedSingle(v) => core.data.set_local_param(&s.0, &v),
-                Value::EvaluatedArray(a) => core.data.set_local_array(&s.0, &a),
-                _ => {},
+    fn exec_set_params(&mut self, core: &mut ShellCore) -> Option<Pid> {
+        self.set_params(core, None);
+
+        if ! self.redirects.is_empty() {
+            match self.redirects.iter_mut().all(|r| r.connect(true, core)) {
+                true  => core.data.set_param("?", "0"),
+                false => core.data.set_param("?", "1"),
             }
+            self.redirects.iter_mut().rev().for_each(|r| r.restore());
         }
+        None
+    }
+
+    fn set_local_params(&mut self, core: &mut ShellCore) {
+        let layer = core.data.get_layer_num() - 1;
+        self.set_params(core, Some(layer));
     }
 
-    fn set_environment_variables(&mut self) {
+    fn set_environment_variables(&mut self, core: &mut ShellCore) {
+        core.set_environment_variables();
+
         for s in &self.evaluated_subs {
-            match &s.1 {
+            match &s.2 {
                 Value::EvaluatedSingle(v) => env::set_var(&s.0, &v),
                 _ => {},
             }
@@ -175,9 +250,15 @@ impl SimpleCommand {
     fn eval_substitutions(&mut self, core: &mut ShellCore) -> bool {
         self.evaluated_subs.clear();
         for s in &mut self.substitutions {
+            let index = s.eval_index(core);
+            if s.index.is_some() && index.is_none() {
+                eprintln!("sush: {}: bad array subscript", &s.text);
+                return false;
+            }
+
             match s.eval(core) {
                 Value::None => return false,
-                a           => self.evaluated_subs.push( (s.key.clone(), a) ),
+                a           => self.evaluated_subs.push( (s.key.clone(), index, a) ),
             }
         }
         true
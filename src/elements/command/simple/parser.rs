@@ -46,7 +46,7 @@ impl SimpleCommand {
         if ans.words.len() == 0 {
             if utils::reserved(&w.text) {
                 return false;
-            }else if w.text == "local" {
+            }else if ["local", "declare", "typeset", "export", "readonly"].contains(&w.text.as_str()) {
                 ans.permit_substitution_arg = true;
             }
         }
@@ -63,7 +63,8 @@ impl SimpleCommand {
     fn set_alias(word: &Word, words: &mut Vec<Word>, text: &mut String,
                  core: &mut ShellCore, feeder: &mut Feeder) -> bool {
         let mut w = word.text.clone();
-        if ! core.data.replace_alias(&mut w) {
+        if ! core.shopts.query("expand_aliases")
+        || ! core.data.replace_alias(&mut w) {
             return false;
         }
 
@@ -8,6 +8,7 @@ use crate::elements::command;
 #[derive(Debug, Clone)]
 pub struct WhileCommand {
     pub text: String,
+    pub keyword: String,
     pub while_script: Option<Script>,
     pub do_script: Option<Script>,
     pub redirects: Vec<Redirect>,
@@ -18,13 +19,12 @@ impl Command for WhileCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) {
         core.loop_level += 1;
         loop {
-            core.suspend_e_option = true;
-            self.while_script.as_mut()
-                .expect(&error_message::internal_str("no script"))
-                .exec(core);
+            let script = self.while_script.as_mut()
+                .expect(&error_message::internal_str("no script"));
+            core.exec_ignoring_errexit(|c| script.exec(c));
 
-            core.suspend_e_option = false;
-            if core.data.get_param("?") != "0" {
+            let success = core.data.get_param("?") == "0";
+            if success == ( self.keyword == "until" ) {
                 core.data.set_param("?", "0");
                 break;
             }
@@ -33,15 +33,11 @@ impl Command for WhileCommand {
                 .expect(&error_message::internal_str("no script"))
                 .exec(core);
 
-            if core.break_counter > 0 {
-                core.break_counter -= 1;
+            if core.check_loop_exit() {
                 break;
             }
         }
-        core.loop_level -= 1;
-        if core.loop_level == 0 {
-            core.break_counter = 0;
-        }
+        core.leave_loop();
     }
 
     fn get_text(&self) -> String { self.text.clone() }
@@ -55,6 +51,7 @@ impl WhileCommand {
     fn new() -> WhileCommand {
         WhileCommand {
             text: String::new(),
+            keyword: String::new(),
             while_script: None,
             do_script: None,
             redirects: vec![],
@@ -64,9 +61,14 @@ impl WhileCommand {
 
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<WhileCommand> {
         let mut ans = Self::new();
-        if command::eat_inner_script(feeder, core, "while", vec!["do"], &mut ans.while_script, false)
+        ans.keyword = match feeder.starts_with("until") {
+            true  => "until".to_string(),
+            false => "while".to_string(),
+        };
+
+        if command::eat_inner_script(feeder, core, &ans.keyword.clone(), vec!["do"], &mut ans.while_script, false)
         && command::eat_inner_script(feeder, core, "do", vec!["done"],  &mut ans.do_script, false) {
-            ans.text.push_str("while");
+            ans.text.push_str(&ans.keyword.clone());
             ans.text.push_str(&ans.while_script.as_mut().unwrap().get_text());
             ans.text.push_str("do");
             ans.text.push_str(&ans.do_script.as_mut().unwrap().get_text());
@@ -28,6 +28,10 @@ impl ArithmeticExpr {
         match self.eval_elems(core, true) {
             Ok(ArithElem::Integer(n)) => self.ans_to_string(n),
             Ok(ArithElem::Float(f))   => Some(f.to_string()),
+            Err(msg) if msg.ends_with(": readonly variable") => {
+                eprintln!("sush: {}", msg);
+                None
+            },
             Err(msg) => {
                 eprintln!("sush: {}: {}", &self.text, msg);
                 None
@@ -52,6 +52,10 @@ pub fn str_to_num(name: &str, core: &mut ShellCore) -> Result<ArithElem, String>
 
     for i in 0..RESOLVE_LIMIT {
         match is_name(&name, core) {
+            true if core.data.flags.contains('u') && ! core.data.is_set(&name) => {
+                core.word_eval_error = true;
+                return Err(format!("{}: unbound variable", &name));
+            },
             true  => name = core.data.get_param(&name),
             false => break,
         }
@@ -80,6 +84,10 @@ fn change_variable(name: &str, core: &mut ShellCore, inc: i64, pre: bool) -> Res
         }
     }
 
+    if inc != 0 && core.data.is_readonly(name) {
+        return Err(error_message::readonly(name));
+    }
+
     match str_to_num(&name, core) {
         Ok(ArithElem::Integer(n))        => {
             core.data.set_param(name, &(n + inc).to_string());
@@ -142,6 +150,10 @@ fn subs(op: &str, w: &Word, right_value: &ArithElem, core: &mut ShellCore)
         None => return Err(format!("{}: wrong substitution", &w.text)),
     };
 
+    if core.data.is_readonly(&name) {
+        return Err(error_message::readonly(&name));
+    }
+
     let right_str = match right_value {
         ArithElem::Integer(n) => n.to_string(),
         ArithElem::Float(f)   => f.to_string(),
@@ -1,18 +1,24 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+mod args;
+
 use crate::{error_message, ShellCore, Feeder};
-use crate::utils::file_check;
+use crate::utils::{file_check, glob};
+use crate::utils::regex::Regex;
+use crate::elements::subword;
+use crate::elements::subword::simple::SimpleSubword;
 use crate::elements::word::Word;
 use super::arithmetic::word;
 use super::arithmetic::elem::ArithElem;
-use std::env;
 
 #[derive(Debug, Clone)]
 pub enum CondElem {
     UnaryOp(String),
     BinaryOp(String),
     Word(Word),
+    Pattern(Word),
+    Regex(Word),
     Operand(String),
     InParen(ConditionalExpr),
     Not, // !
@@ -36,7 +42,7 @@ pub fn to_string(op: &CondElem) -> String {
         CondElem::UnaryOp(op) => op.to_string(),
         CondElem::BinaryOp(op) => op.to_string(),
         CondElem::InParen(expr) => expr.text.clone(),
-        CondElem::Word(w) => w.text.clone(),
+        CondElem::Word(w) | CondElem::Pattern(w) | CondElem::Regex(w) => w.text.clone(),
         CondElem::Operand(op) => op.to_string(),
         CondElem::Not => "!".to_string(),
         CondElem::And => "&&".to_string(),
@@ -57,6 +63,10 @@ fn pop_operand(stack: &mut Vec<CondElem>, core: &mut ShellCore) -> Result<CondEl
     match stack.pop() {
         Some(CondElem::InParen(mut expr)) => expr.eval(core),
         Some(CondElem::Word(w)) => to_operand(&w, core),
+        Some(CondElem::Regex(w)) => match w.eval_for_regex(core) {
+            Some(v) => Ok(CondElem::Operand(v)),
+            None => Err(format!("{}: wrong substitution", &w.text)),
+        },
         Some(elem) => Ok(elem),
         None => return Err("no operand".to_string()),
     }
@@ -119,7 +129,8 @@ impl ConditionalExpr {
     
         for e in elems {
             let ok = match e {
-                CondElem::Word(_) | CondElem::InParen(_) => {ans.push(e.clone()); true},
+                CondElem::Word(_) | CondElem::Pattern(_) | CondElem::Regex(_)
+                | CondElem::InParen(_) | CondElem::Operand(_) => {ans.push(e.clone()); true},
                 op               => Self::rev_polish_op(&op, &mut stack, &mut ans),
             };
     
@@ -141,7 +152,8 @@ impl ConditionalExpr {
 
         for e in rev_pol {
             let result = match e { 
-                CondElem::Word(_) | CondElem::InParen(_) => {
+                CondElem::Word(_) | CondElem::Pattern(_) | CondElem::Regex(_)
+                | CondElem::InParen(_) | CondElem::Operand(_) => {
                     stack.push(e.clone());
                     Ok(())
                 },
@@ -190,8 +202,8 @@ impl ConditionalExpr {
 
         if op == "-o" || op == "-v" || op == "-z" || op == "-n" {
             let ans = match op {
-                "-o" => core.options.query(&operand),
-                "-v" => core.data.get_value(&operand).is_some() || env::var(&operand).is_ok(),
+                "-o" => core.query_option(&operand),
+                "-v" => core.data.is_set(&operand),
                 "-z" => operand.len() == 0,
                 "-n" => operand.len() > 0,
                 _    => false,
@@ -205,6 +217,11 @@ impl ConditionalExpr {
     }
 
     fn bin_operation(op: &str, stack: &mut Vec<CondElem>, core: &mut ShellCore) -> Result<(), String> {
+        if let Some(CondElem::Pattern(w)) = stack.last().cloned() {
+            stack.pop();
+            return Self::pattern_match(op, &w, stack, core);
+        }
+
         let right = match pop_operand(stack, core) {
             Ok(CondElem::Operand(name)) => name,
             Ok(_)  => return Err("Invalid operand".to_string()),
@@ -230,6 +247,10 @@ impl ConditionalExpr {
             return Ok(());
         }
 
+        if op == "=~" {
+            return Self::regex_match(&left, &right, stack, core);
+        }
+
         if op == "-eq" || op == "-ne" || op == "-lt" || op == "-le" || op == "-gt" || op == "-ge" {
             let lnum = match word::str_to_num(&left, core) {
                 Ok(ArithElem::Integer(n)) => n,
@@ -261,6 +282,42 @@ impl ConditionalExpr {
         Ok(())
     }
 
+    fn pattern_match(op: &str, w: &Word, stack: &mut Vec<CondElem>,
+                     core: &mut ShellCore) -> Result<(), String> {
+        let pattern = match w.eval_for_case_pattern(core) {
+            Some(p) => p,
+            None    => return Err(format!("{}: wrong substitution", &w.text)),
+        };
+        let left = match pop_operand(stack, core) {
+            Ok(CondElem::Operand(name)) => name,
+            Ok(_)  => return Err("Invalid operand".to_string()),
+            Err(e) => return Err(e),
+        };
+
+        let extglob = core.shopts.query("extglob");
+        let ans = match core.shopts.query("nocasematch") {
+            true  => glob::compare_nocase(&left, &pattern, extglob),
+            false => glob::compare(&left, &pattern, extglob),
+        };
+
+        stack.push( CondElem::Ans(ans == (op != "!=")) );
+        Ok(())
+    }
+
+    fn regex_match(s: &str, pattern: &str, stack: &mut Vec<CondElem>,
+                   core: &mut ShellCore) -> Result<(), String> {
+        let icase = core.shopts.query("nocasematch");
+        let matched = match Regex::new(pattern, icase) {
+            Ok(re) => re.captures(s),
+            Err(e) => return Err(format!("{}: {}", pattern, e)),
+        };
+
+        let ans = matched.is_some();
+        core.data.set_array("BASH_REMATCH", &matched.unwrap_or_default());
+        stack.push( CondElem::Ans(ans) );
+        Ok(())
+    }
+
     fn unary_file_check(op: &str, s: &String, stack: &mut Vec<CondElem>) -> Result<(), String> {
         let result = match op {
             "-a" | "-e"  => file_check::exists(s),
@@ -335,8 +392,72 @@ impl ConditionalExpr {
 
         let opt = feeder.consume(len);
         ans.text += &opt.clone();
-        ans.elements.push(CondElem::BinaryOp(opt));
+        ans.elements.push(CondElem::BinaryOp(opt.clone()));
+
+        if opt == "=~" {
+            Self::eat_blank(feeder, ans, core);
+            return Self::eat_regex(feeder, ans, core);
+        }
+        if opt == "==" || opt == "=" || opt == "!=" {
+            Self::eat_blank(feeder, ans, core);
+            Self::eat_pattern(feeder, ans, core);
+        }
+        true
+    }
+
+    fn eat_pattern(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        if feeder.starts_with("]]") {
+            return false;
+        }
+
+        match Word::parse(feeder, core, false) {
+            Some(w) => {
+                ans.text += &w.text.clone();
+                ans.elements.push(CondElem::Pattern(w));
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn eat_regex(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        let mut word = Word::new();
+        let mut depth = 0;
+        loop {
+            if depth == 0 && feeder.starts_with("]]") {
+                break;
+            }
+
+            let sym_len = match feeder.scanner_regex_bracket() {
+                0 => feeder.scanner_regex_symbol(depth > 0),
+                n => n,
+            };
+
+            let sw: Box<dyn subword::Subword> = if sym_len > 0 {
+                let text = feeder.consume(sym_len);
+                match text.as_str() {
+                    "(" => depth += 1,
+                    ")" => depth -= 1,
+                    _   => {},
+                }
+                Box::new(SimpleSubword{ text })
+            }else{
+                match subword::parse(feeder, core) {
+                    Some(sw) => sw,
+                    None     => break,
+                }
+            };
+
+            word.text += sw.get_text();
+            word.subwords.push(sw);
+        }
+
+        if word.subwords.is_empty() || depth != 0 {
+            return false;
+        }
 
+        ans.text += &word.text.clone();
+        ans.elements.push(CondElem::Regex(word));
         true
     }
 
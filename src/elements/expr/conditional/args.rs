@@ -0,0 +1,184 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{CondElem, ConditionalExpr};
+
+const UNARY_OPS: [&str; 25] = ["-a", "-b", "-c", "-d", "-e", "-f", "-g", "-h", "-k",
+                               "-n", "-o", "-p", "-r", "-s", "-t", "-u", "-v", "-w",
+                               "-x", "-z", "-G", "-L", "-N", "-O", "-S"];
+const BINARY_OPS: [&str; 14] = ["=", "==", "!=", "<", ">", "-eq", "-ne", "-lt",
+                                "-le", "-gt", "-ge", "-ef", "-nt", "-ot"];
+
+fn is_unary(s: &str) -> bool { UNARY_OPS.contains(&s) }
+fn is_binary(s: &str) -> bool { BINARY_OPS.contains(&s) }
+
+/* operands of test are always decimal (e.g. 010 is ten) */
+fn to_decimal(s: &str) -> Option<String> {
+    let s = s.trim();
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if digits.is_empty() || ! digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    s.parse::<i64>().ok().map(|n| n.to_string())
+}
+
+impl ConditionalExpr {
+    fn from_elems(args: &[String], mut elements: Vec<CondElem>) -> Self {
+        elements.push(CondElem::And);
+        ConditionalExpr {
+            text: args.join(" "),
+            elements,
+        }
+    }
+
+    fn one_arg(args: &[String]) -> Self {
+        Self::from_elems(args, vec![CondElem::Operand(args[0].clone())])
+    }
+
+    fn negation(args: &[String], expr: Self) -> Self {
+        Self::from_elems(args, vec![CondElem::Not, CondElem::InParen(expr)])
+    }
+
+    fn unary(args: &[String]) -> Self {
+        let elems = vec![CondElem::UnaryOp(args[0].clone()), CondElem::Operand(args[1].clone())];
+        Self::from_elems(args, elems)
+    }
+
+    fn binary(args: &[String]) -> Result<Self, String> {
+        let op = args[1].as_str();
+        let mut operands = [args[0].clone(), args[2].clone()];
+        if op.starts_with("-") && ! ["-ef", "-nt", "-ot"].contains(&op) {
+            for a in operands.iter_mut() {
+                match to_decimal(a) {
+                    Some(n) => *a = n,
+                    None    => return Err(format!("{}: integer expression expected", a)),
+                }
+            }
+        }
+
+        let [left, right] = operands;
+        let elems = vec![CondElem::Operand(left),
+                         CondElem::BinaryOp(args[1].clone()),
+                         CondElem::Operand(right)];
+        Ok(Self::from_elems(args, elems))
+    }
+
+    fn two_args(args: &[String]) -> Result<Self, String> {
+        if args[0] == "!" {
+            return Ok(Self::negation(args, Self::one_arg(&args[1..])));
+        }
+        match is_unary(&args[0]) {
+            true  => Ok(Self::unary(args)),
+            false => Err(format!("{}: unary operator expected", &args[0])),
+        }
+    }
+
+    fn three_args(args: &[String]) -> Result<Self, String> {
+        if is_binary(&args[1]) {
+            return Self::binary(args);
+        }
+        if args[1] == "-a" || args[1] == "-o" {
+            let op = match args[1].as_str() {
+                "-a" => CondElem::And,
+                _    => CondElem::Or,
+            };
+            let elems = vec![CondElem::Operand(args[0].clone()), op, CondElem::Operand(args[2].clone())];
+            return Ok(Self::from_elems(args, elems));
+        }
+        if args[0] == "!" {
+            return Ok(Self::negation(args, Self::two_args(&args[1..])?));
+        }
+        if args[0] == "(" && args[2] == ")" {
+            return Ok(Self::one_arg(&args[1..2]));
+        }
+        Err(format!("{}: binary operator expected", &args[1]))
+    }
+
+    fn four_args(args: &[String]) -> Result<Self, String> {
+        if args[0] == "!" {
+            return Ok(Self::negation(args, Self::three_args(&args[1..])?));
+        }
+        if args[0] == "(" && args[3] == ")" {
+            return Self::two_args(&args[1..3]);
+        }
+        Self::parse_expr(args)
+    }
+
+    /* recursive descent for five or more arguments: -o < -a < ! < ( ) */
+    fn parse_expr(args: &[String]) -> Result<Self, String> {
+        let mut pos = 0;
+        let ans = Self::or_expr(args, &mut pos)?;
+        match pos < args.len() {
+            true  => Err("too many arguments".to_string()),
+            false => Ok(ans),
+        }
+    }
+
+    fn or_expr(args: &[String], pos: &mut usize) -> Result<Self, String> {
+        Self::list_expr(args, pos, "-o", CondElem::Or, Self::and_expr)
+    }
+
+    fn and_expr(args: &[String], pos: &mut usize) -> Result<Self, String> {
+        Self::list_expr(args, pos, "-a", CondElem::And, Self::term)
+    }
+
+    fn list_expr(args: &[String], pos: &mut usize, sep: &str, op: CondElem,
+                 sub: fn(&[String], &mut usize) -> Result<Self, String>) -> Result<Self, String> {
+        let start = *pos;
+        let mut elems = vec![CondElem::InParen(sub(args, pos)?)];
+        while *pos < args.len() && args[*pos] == sep {
+            *pos += 1;
+            elems.push(op.clone());
+            elems.push(CondElem::InParen(sub(args, pos)?));
+        }
+        Ok(Self::from_elems(&args[start..*pos], elems))
+    }
+
+    fn term(args: &[String], pos: &mut usize) -> Result<Self, String> {
+        let start = *pos;
+        let rest = &args[start..];
+        if rest.is_empty() {
+            return Err("argument expected".to_string());
+        }
+
+        if rest[0] == "!" {
+            *pos += 1;
+            let expr = Self::term(args, pos)?;
+            return Ok(Self::negation(&args[start..*pos], expr));
+        }
+
+        if rest[0] == "(" {
+            *pos += 1;
+            let expr = Self::or_expr(args, pos)?;
+            if args.get(*pos).map(|s| s.as_str()) != Some(")") {
+                return Err("`)' expected".to_string());
+            }
+            *pos += 1;
+            return Ok(expr);
+        }
+
+        if rest.len() >= 3 && is_binary(&rest[1]) {
+            *pos += 3;
+            return Self::binary(&rest[..3]);
+        }
+
+        if rest.len() >= 2 && is_unary(&rest[0]) {
+            *pos += 2;
+            return Ok(Self::unary(&rest[..2]));
+        }
+
+        *pos += 1;
+        Ok(Self::one_arg(&rest[..1]))
+    }
+
+    pub fn parse_args(args: &[String]) -> Result<Self, String> {
+        match args.len() {
+            0 => Ok(Self::from_elems(args, vec![CondElem::Operand(String::new())])),
+            1 => Ok(Self::one_arg(args)),
+            2 => Self::two_args(args),
+            3 => Self::three_args(args),
+            4 => Self::four_args(args),
+            _ => Self::parse_expr(args),
+        }
+    }
+}
@@ -21,6 +21,9 @@ pub fn replace(from: RawFd, to: RawFd) -> bool {
     if from < 0 || to < 0 {
         return false;
     }
+    if from == to { //dup2 is skipped, so the close-on-exec flag of File::open is cleared here
+        return fcntl::fcntl(to, fcntl::F_SETFD(fcntl::FdFlag::empty())).is_ok();
+    }
 
     match unistd::dup2(from, to) {
         Ok(_) => {
@@ -57,8 +60,11 @@ fn share(from: RawFd, to: RawFd) -> bool {
 }
 
 pub fn backup(from: RawFd) -> RawFd {
-    fcntl::fcntl(from, fcntl::F_DUPFD_CLOEXEC(10))
-           .expect("Can't allocate fd for backup")
+    match fcntl::fcntl(from, fcntl::F_DUPFD_CLOEXEC(10)) {
+        Ok(fd) => fd,
+        Err(Errno::EBADF) => -1, //not opened
+        Err(e) => panic!("Can't allocate fd for backup: {}", e),
+    }
 }
 
 pub fn connect(pipe: &mut Pipe, rs: &mut Vec<Redirect>, core: &mut ShellCore) {
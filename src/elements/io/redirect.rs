@@ -1,12 +1,77 @@
 //SPDX-FileCopyrightText: 2023 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use std::{env, process};
 use std::fs::{File, OpenOptions};
-use std::os::fd::{IntoRawFd, RawFd};
-use std::io::Error;
+use std::os::fd::{FromRawFd, IntoRawFd, RawFd};
+use std::io::{Error, ErrorKind, Seek, SeekFrom, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::elements::io;
-use crate::elements::word::Word;
+use crate::elements::subscript::Subscript;
+use nix::{fcntl, unistd};
+use crate::elements::subword::double_quoted::DoubleQuoted;
+use crate::elements::subword::simple::SimpleSubword;
+use crate::elements::word::{substitution, Word};
 use crate::{error_message, Feeder, ShellCore};
+use crate::utils::file_check;
+
+static HERE_DOCUMENT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn invalid_input(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, msg)
+}
+
+fn open_socket(addr: &str, udp: bool) -> Result<File, Error> {
+    let (host, port) = addr.split_once('/').ok_or(invalid_input("no port"))?;
+    let port = port.parse::<u16>().map_err(|_| invalid_input("invalid port"))?;
+    let addrs = (host, port).to_socket_addrs()?.collect::<Vec<SocketAddr>>();
+
+    let fd = match udp {
+        false => TcpStream::connect(&addrs[..])?.into_raw_fd(),
+        true  => {
+            let addr = addrs.first().ok_or(invalid_input("unknown host"))?;
+            let local = match addr.is_ipv4() {
+                true  => "0.0.0.0:0",
+                false => "[::]:0",
+            };
+            let socket = UdpSocket::bind(local)?;
+            socket.connect(addr)?;
+            socket.into_raw_fd()
+        },
+    };
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+fn special_fd(path: &str) -> Option<RawFd> {
+    match path {
+        "/dev/stdin"  => Some(0),
+        "/dev/stdout" => Some(1),
+        "/dev/stderr" => Some(2),
+        _ => path.strip_prefix("/dev/fd/")?.parse::<RawFd>().ok(),
+    }
+}
+
+/* /dev/tcp/host/port and /dev/udp/host/port are sockets.
+   /dev/std{in,out,err} and /dev/fd/N are emulated if they don't exist */
+fn open_file(path: &str, options: &OpenOptions) -> Result<File, Error> {
+    if let Some(addr) = path.strip_prefix("/dev/tcp/") {
+        return open_socket(addr, false);
+    }
+    if let Some(addr) = path.strip_prefix("/dev/udp/") {
+        return open_socket(addr, true);
+    }
+
+    if let Some(fd) = special_fd(path) {
+        if ! Path::new(path).exists() {
+            let newfd = unistd::dup(fd)?;
+            return Ok(unsafe { File::from_raw_fd(newfd) });
+        }
+    }
+
+    options.open(path)
+}
 
 #[derive(Debug, Clone)]
 pub struct Redirect {
@@ -14,13 +79,46 @@ pub struct Redirect {
     pub symbol: String,
     pub right: Word,
     pub left: String,
+    left_var: Option<String>, // {varname}
+    left_var_subscript: Option<Subscript>, // {varname[subscript]}
+    here_data: Option<Word>,
     left_fd: RawFd,
-    left_backup: RawFd,
-    extra_left_backup: RawFd, // &>, &>>用
+    backups: Vec<(RawFd, RawFd)>, // (fd, its backup or -1 if it was not open)
 }
 
 impl Redirect {
     pub fn connect(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        let name = match self.left_var.clone() {
+            Some(name) => name,
+            None       => return self.connect_fd(restore, core),
+        };
+
+        let index = match self.set_left_var(&name, core) {
+            Some(index) => index,
+            None        => return false,
+        };
+
+        if ! self.connect_fd(false, core) { //the fd persists after the command
+            return false;
+        }
+
+        /* the variable is assigned only after the fd is opened */
+        if ! self.closes_left_var() {
+            match &index {
+                Some(i) => {core.data.set_layer_array_elem(&name, i, &self.left, 0);},
+                None    => core.data.set_param(&name, &self.left),
+            }
+        }
+        true
+    }
+
+    fn connect_fd(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        match self.symbol.as_str() {
+            "<<" | "<<-" => return self.redirect_here_document(restore, core),
+            "<<<" => return self.redirect_here_string(restore, core),
+            _ => {},
+        }
+
         let args = match self.right.eval(core) {
             Some(v) => v,
             None => return false,
@@ -33,16 +131,36 @@ impl Redirect {
             self.right.text = args[0].clone();
         }
 
+        if core.data.flags.contains('C') && self.clobbers() {
+            eprintln!("sush: {}: cannot overwrite existing file", &self.right.text);
+            return false;
+        }
+
         match self.symbol.as_str() {
             "<" => self.redirect_simple_input(restore),
-            ">" => self.redirect_simple_output(restore),
-            ">&" => self.redirect_output_fd(restore),
+            ">" | ">|" => self.redirect_simple_output(restore),
             ">>" => self.redirect_append(restore),
-            "&>" => self.redirect_both_output(restore),
+            "<>" => self.redirect_read_write(restore),
+            "&>" => self.redirect_both_output(restore, false),
+            "&>>" => self.redirect_both_output(restore, true),
+            ">&" if self.left.is_empty() && ! Self::is_fd_word(&self.right.text)
+                 => self.redirect_both_output(restore, false),
+            ">&" => self.redirect_dup(1, restore),
+            "<&" => self.redirect_dup(0, restore),
             _ => error_message::internal(" (Unknown redirect symbol)"),
         }
     }
 
+    /* noclobber protects regular files from >, &> and >&word but not from >| */
+    fn clobbers(&self) -> bool {
+        let truncate = match self.symbol.as_str() {
+            ">" | "&>" => true,
+            ">&" => self.left.is_empty() && ! Self::is_fd_word(&self.right.text),
+            _ => false,
+        };
+        truncate && file_check::is_regular_file(&self.right.text)
+    }
+
     fn set_left_fd(&mut self, default_fd: RawFd) {
         self.left_fd = if self.left.len() == 0 {
             default_fd
@@ -52,23 +170,81 @@ impl Redirect {
         };
     }
 
-    fn connect_to_file(&mut self, file_open_result: Result<File,Error>, restore: bool) -> bool {
+    fn free_fd() -> RawFd {
+        let mut fd = 10;
+        while fcntl::fcntl(fd, fcntl::F_GETFD).is_ok() {
+            fd += 1;
+        }
+        fd
+    }
+
+    fn closes_left_var(&self) -> bool {
+        self.right.text == "-" && (self.symbol == ">&" || self.symbol == "<&")
+    }
+
+    /* {varname}>&- closes the fd in the variable
+       and the other forms allocate a new fd to the variable.
+       The evaluated subscript of {varname[subscript]} is returned. */
+    fn set_left_var(&mut self, name: &str, core: &mut ShellCore) -> Option<Option<String>> {
+        let index = match self.left_var_subscript.as_mut() {
+            Some(sub) => match sub.eval(core, name) {
+                Some(i) => Some(i),
+                None    => {
+                    eprintln!("sush: {}{}: bad array subscript", name, &sub.text);
+                    return None;
+                },
+            },
+            None => None,
+        };
+
+        if self.closes_left_var() {
+            self.left = match &index {
+                Some(i) => core.data.get_array(name, i),
+                None    => core.data.get_param(name),
+            };
+            if self.left.is_empty() {
+                eprintln!("sush: {}: ambiguous redirect", name);
+                return None;
+            }
+            if self.left.parse::<RawFd>().is_err() {
+                self.left = "-1".to_string();
+            }
+            return Some(index);
+        }
+
+        if core.data.is_readonly(name) {
+            eprintln!("sush: {}: readonly variable", name);
+            eprintln!("sush: {}: cannot assign fd to variable", name);
+            return None;
+        }
+
+        self.left = Self::free_fd().to_string();
+        Some(index)
+    }
+
+    fn backup(&mut self, fd: RawFd, restore: bool) {
         if restore {
-            self.left_backup = io::backup(self.left_fd);
+            self.backups.push((fd, io::backup(fd)));
         }
+    }
 
+    fn connect_to_file(&mut self, file_open_result: Result<File,Error>, restore: bool) -> bool {
         match file_open_result {
             Ok(file) => {
                 let fd = file.into_raw_fd();
+                if fd != self.left_fd {
+                    self.backup(self.left_fd, restore);
+                }else if restore { //the left fd was not open
+                    self.backups.push((fd, -1));
+                }
                 let result = io::replace(fd, self.left_fd);
                 if ! result {
                     io::close(fd, &format!("sush(fatal): file does not close"));
-                    self.left_fd = -1;
                 }
                 result
             },
-            _  => {
-                eprintln!("sush: {}: {}", &self.right.text, Error::last_os_error().kind());
+            Err(e) => {
+                eprintln!("sush: {}: {}", &self.right.text, e.kind());
                 false
             },
         }
@@ -76,49 +252,140 @@ impl Redirect {
 
     fn redirect_simple_input(&mut self, restore: bool) -> bool {
         self.set_left_fd(0);
-        self.connect_to_file(File::open(&self.right.text), restore)
+        self.connect_to_file(open_file(&self.right.text, OpenOptions::new().read(true)), restore)
     }
 
     fn redirect_simple_output(&mut self, restore: bool) -> bool {
         self.set_left_fd(1);
-        self.connect_to_file(File::create(&self.right.text), restore)
+        self.connect_to_file(open_file(&self.right.text, &Self::create_options()), restore)
+    }
+
+    fn redirect_read_write(&mut self, restore: bool) -> bool {
+        self.set_left_fd(0);
+        self.connect_to_file(open_file(&self.right.text, OpenOptions::new().create(true)
+                .read(true).write(true).truncate(false)), restore)
+    }
+
+    fn create_options() -> OpenOptions {
+        let mut options = OpenOptions::new();
+        options.create(true).write(true).truncate(true);
+        options
+    }
+
+    fn is_fd_word(word: &str) -> bool {
+        let num = word.strip_suffix("-").unwrap_or(word);
+        num.is_empty() || num.chars().all(|c| c.is_ascii_digit())
     }
 
-    fn redirect_output_fd(&mut self, _: bool) -> bool {
-        let fd = match self.right.text.parse::<RawFd>() {
+    /* n>&m, n<&m, n>&m- (move) and n>&-, n<&- (close) */
+    fn redirect_dup(&mut self, default_fd: RawFd, restore: bool) -> bool {
+        self.set_left_fd(default_fd);
+
+        let word = self.right.text.clone();
+        let (num, move_fd) = match word.strip_suffix("-") {
+            Some(n) => (n, true),
+            None    => (word.as_str(), false),
+        };
+
+        if num.is_empty() && move_fd {
+            self.backup(self.left_fd, restore);
+            let _ = unistd::close(self.left_fd);
+            return true;
+        }
+
+        let fd = match num.parse::<RawFd>() {
             Ok(n) => n,
-            _     => return false,
+            _     => {
+                eprintln!("sush: {}: ambiguous redirect", &word);
+                return false;
+            },
         };
 
-        self.set_left_fd(1);
-        io::share(fd, self.left_fd)
+        if fcntl::fcntl(fd, fcntl::F_GETFD).is_err() {
+            eprintln!("sush: {}: Bad file descriptor", fd);
+            return false;
+        }
+        if fd == self.left_fd {
+            return true;
+        }
+
+        self.backup(self.left_fd, restore);
+        if ! io::share(fd, self.left_fd) {
+            return false;
+        }
+
+        if move_fd {
+            self.backup(fd, restore);
+            let _ = unistd::close(fd);
+        }
+        true
     }
 
     fn redirect_append(&mut self, restore: bool) -> bool {
         self.set_left_fd(1);
-        self.connect_to_file(OpenOptions::new().create(true)
-                .write(true).append(true).open(&self.right.text), restore)
+        self.connect_to_file(open_file(&self.right.text, OpenOptions::new().create(true)
+                .append(true)), restore)
     }
 
-    fn redirect_both_output(&mut self, restore: bool) -> bool {
+    fn redirect_both_output(&mut self, restore: bool, append: bool) -> bool {
         self.left_fd = 1;
-        if ! self.connect_to_file(File::create(&self.right.text), restore){
+        let file = match append {
+            true  => open_file(&self.right.text, OpenOptions::new().create(true).append(true)),
+            false => open_file(&self.right.text, &Self::create_options()),
+        };
+        if ! self.connect_to_file(file, restore){
             return false;
         }
 
-        if restore {
-            self.extra_left_backup = io::backup(2);
-        }
+        self.backup(2, restore);
         io::share(1, 2);
         true
     }
 
-    pub fn restore(&mut self) {
-        if self.left_backup >= 0 && self.left_fd >= 0 {
-            io::replace(self.left_backup, self.left_fd);
+    fn open_here_data(data: &str) -> Result<File, Error> {
+        let num = HERE_DOCUMENT_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("sush_here_{}_{}", process::id(), num));
+
+        let mut file = OpenOptions::new().read(true).write(true)
+                       .create_new(true).open(&path)?;
+        let _ = std::fs::remove_file(&path);
+        file.write_all(data.as_bytes())?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(file)
+    }
+
+    fn redirect_here_document(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        let mut word = match self.here_data.clone() {
+            Some(w) => w,
+            None    => return false,
+        };
+
+        if ! substitution::eval(&mut word, core) {
+            return false;
         }
-        if self.extra_left_backup >= 0 {
-            io::replace(self.extra_left_backup, 2);
+
+        let data = word.make_unquoted_word().unwrap_or_default();
+        self.set_left_fd(0);
+        self.connect_to_file(Self::open_here_data(&data), restore)
+    }
+
+    fn redirect_here_string(&mut self, restore: bool, core: &mut ShellCore) -> bool {
+        let data = match self.right.tilde_and_dollar_expansion(core) {
+            Some(mut w) => w.make_unquoted_word().unwrap_or_default() + "\n",
+            None        => return false,
+        };
+
+        self.set_left_fd(0);
+        self.connect_to_file(Self::open_here_data(&data), restore)
+    }
+
+    /* fds that were not open before the redirection are closed */
+    pub fn restore(&mut self) {
+        while let Some((fd, backup)) = self.backups.pop() {
+            match backup >= 0 {
+                true  => { io::replace(backup, fd); },
+                false => { let _ = unistd::close(fd); },
+            }
         }
     }
 
@@ -128,9 +395,11 @@ impl Redirect {
             symbol: String::new(),
             right: Word::new(),
             left: String::new(),
+            left_var: None,
+            left_var_subscript: None,
+            here_data: None,
             left_fd: -1,
-            left_backup: -1,
-            extra_left_backup: -1,
+            backups: vec![],
         }
     }
 
@@ -159,7 +428,71 @@ impl Redirect {
         true
     }
 
+    fn remove_line_continuation(body: &str) -> String {
+        let mut ans = String::new();
+        let mut escaped = false;
+        for c in body.chars() {
+            if escaped && c == '\n' {
+                ans.pop();
+            }else{
+                ans.push(c);
+            }
+            escaped = ! escaped && c == '\\';
+        }
+        ans
+    }
+
+    fn eat_here_document(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        let quoted = ans.right.text.contains(['\'', '"', '\\']);
+        let end_mark = match ans.right.clone().make_unquoted_word() {
+            Some(s) => s,
+            None    => return false,
+        };
+
+        let body = match feeder.cut_here_document(&end_mark, ans.symbol == "<<-", core) {
+            Some(b) => b,
+            None    => return false,
+        };
+
+        let mut word = Word::new();
+        if quoted {
+            word.subwords.push(Box::new(SimpleSubword{ text: body }));
+        }else{
+            let body = Self::remove_line_continuation(&body);
+            let dq = DoubleQuoted::parse_here_document(&mut Feeder::new(&body), core);
+            word.subwords.push(Box::new(dq));
+        }
+        ans.here_data = Some(word);
+        true
+    }
+
+    fn eat_left_var(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        ans.text += &feeder.consume(1);
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return false;
+        }
+        let name = feeder.consume(len);
+        ans.text += &name;
+
+        if let Some(s) = Subscript::parse(feeder, core) {
+            ans.text += &s.text;
+            ans.left_var_subscript = Some(s);
+        }
+
+        if ! feeder.starts_with("}") {
+            return false;
+        }
+        ans.text += &feeder.consume(1);
+        ans.left_var = Some(name);
+        true
+    }
+
     fn eat_left(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        if feeder.starts_with("{") {
+            return Self::eat_left_var(feeder, ans, core);
+        }
+
         let len = feeder.scanner_uint(core);
         if len == 0 {
             return true; //左側なし（文法上OK）
@@ -181,6 +514,11 @@ impl Redirect {
         if Self::eat_left(feeder, &mut ans, core) &&
            Self::eat_symbol(feeder, &mut ans, core) &&
            Self::eat_right(feeder, &mut ans, core) {
+            if ans.symbol.starts_with("<<") && ans.symbol != "<<<"
+            && ! Self::eat_here_document(feeder, &mut ans, core) {
+                feeder.rewind();
+                return None;
+            }
             feeder.pop_backup();
             Some(ans)
         }else{
@@ -17,6 +17,10 @@ pub struct Job {
 
 impl Job {
     pub fn exec(&mut self, core: &mut ShellCore, bg: bool) {
+        if Self::noexec(core) {
+            return;
+        }
+
         let pgid = if core.is_subshell { //17〜21行目を追加
             unistd::getpgrp() //自身のPGID
         }else{
@@ -31,23 +35,37 @@ impl Job {
 
     fn exec_fg(&mut self, core: &mut ShellCore, pgid: Pid) {
         let mut do_next = true;
-        let susp_e_option = core.suspend_e_option;
+        let errexit_ignored = core.errexit_ignored;
         for (pipeline, end) in self.pipelines.iter_mut().zip(self.pipeline_ends.iter()) {
-            if core.word_eval_error {
-                return;
+            if core.word_eval_error || core.return_flag || Self::noexec(core)
+            || core.break_counter > 0 || core.continue_counter > 0 {
+                break;
             }
 
-            core.suspend_e_option = susp_e_option || end == "&&" || end == "||";
+            core.errexit_ignored = errexit_ignored || end == "&&" || end == "||";
 
             if do_next {
+                core.errexit_exempted = false;
                 core.jobtable_check_status();
+                if core.data.flags.contains('b') && core.data.flags.contains('m') {
+                    core.jobtable_print_status_change(); //notify
+                }
+                let proc_subst_mark = core.proc_substs.len();
                 let (pids, exclamation, time) = pipeline.exec(core, pgid);
                 let waitstatuses = core.wait_pipeline(pids.clone(), exclamation, time);
+                core.close_proc_substs(proc_subst_mark);
 
                 Self::check_stop(core, &pipeline.text, &pids, &waitstatuses);
+                core.check_trapped_signals();
             }
+            core.errexit_exempted |= ! do_next; //$? of the skipped last member
             do_next = (core.data.get_param("?") == "0") == (end == "&&");
         }
+        core.errexit_ignored = errexit_ignored;
+    }
+
+    fn noexec(core: &ShellCore) -> bool {
+        core.data.flags.contains('n') && ! core.data.flags.contains('i')
     }
 
     fn check_stop(core: &mut ShellCore, text: &str,
@@ -74,6 +92,7 @@ impl Job {
         };
         core.tty_fd = None;
 
+        let proc_subst_mark = core.proc_substs.len();
         let pids = if self.pipelines.len() == 1 {
             if self.pipelines[0].commands.len() == 1 {
                 self.pipelines[0].commands[0].set_force_fork();
@@ -89,6 +108,7 @@ impl Job {
         core.job_table.push(JobEntry::new(pids, &vec![ WaitStatus::StillAlive; len ],
                 &self.text, "Running", new_job_id));
 
+        core.close_proc_substs(proc_subst_mark);
         core.tty_fd = backup;
     }
 
@@ -5,10 +5,12 @@ use crate::{error_message, Feeder, ShellCore};
 use super::command;
 use super::command::Command;
 use super::Pipe;
+use super::io;
 use nix::time;
 use nix::sys::resource;
 use nix::time::ClockId;
 use nix::unistd::Pid;
+use std::os::fd::RawFd;
 use std::sync::atomic::Ordering::Relaxed;
 
 #[derive(Debug, Clone)]
@@ -33,6 +35,11 @@ impl Pipeline {
             return (vec![], self.exclamation, self.time);
         }
 
+        core.run_debug_trap(self.text.trim_end());
+
+        let errexit_ignored = core.errexit_ignored;
+        core.errexit_ignored |= self.exclamation;
+
         let mut prev = -1;
         let mut pids = vec![];
         let mut pgid = pgid;
@@ -49,13 +56,28 @@ impl Pipeline {
             core.word_eval_error = false;
         }
 
-        pids.push(
-            self.commands[self.pipes.len()].exec(core, &mut Pipe::end(prev, pgid))
-        );
+        let last = self.pipes.len();
+        match last > 0 && core.shopts.query("lastpipe") && ! core.data.flags.contains('m') {
+            true  => pids.push( self.exec_lastpipe(core, prev, pgid) ),
+            false => pids.push( self.commands[last].exec(core, &mut Pipe::end(prev, pgid)) ),
+        }
+        core.errexit_ignored = errexit_ignored;
 
         (pids, self.exclamation, self.time)
     }
 
+    /* the last command runs in this shell with the pipe as stdin */
+    fn exec_lastpipe(&mut self, core: &mut ShellCore, prev: RawFd, pgid: Pid) -> Option<Pid> {
+        let backup = io::backup(0);
+        io::replace(prev, 0);
+        let pid = self.commands[self.pipes.len()].exec(core, &mut Pipe::end(-1, pgid));
+        match backup {
+            -1 => io::close(0, "Cannot close stdin"),
+            fd => { io::replace(fd, 0); },
+        }
+        pid
+    }
+
     fn set_time(&mut self, core: &mut ShellCore) {
         if ! self.time {
             return;
@@ -2,6 +2,8 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::elements::command::arithmetic::ArithmeticCommand;
+use crate::elements::word::Word;
 
 #[derive(Debug, Clone)]
 pub struct Subscript {
@@ -9,19 +11,37 @@ pub struct Subscript {
 }
 
 impl Subscript {
-    pub fn eval(&mut self) -> Option<String> {
+    pub fn eval(&mut self, core: &mut ShellCore, name: &str) -> Option<String> {
         let len = self.text.len();
         let inner = &self.text[1..len-1];
 
-        if inner.len() == 1 {
-            if let Some(ch) = inner.chars().nth(0) {
-                if '0' <= ch && ch <= '9' || ch == '@' {
-                    return Some(inner.to_string());
-                }
-            }
+        if inner == "@" || inner == "*" {
+            return Some(inner.to_string());
         }
 
-        None
+        match core.data.is_assoc(name) {
+            true  => Self::eval_as_key(inner, core),
+            false => Self::eval_as_index(inner, name, core),
+        }
+    }
+
+    fn eval_as_key(inner: &str, core: &mut ShellCore) -> Option<String> {
+        match Word::parse(&mut Feeder::new(inner), core, false) {
+            Some(w) => w.eval_for_case_word(core),
+            None    => Some(inner.to_string()),
+        }
+    }
+
+    fn eval_as_index(inner: &str, name: &str, core: &mut ShellCore) -> Option<String> {
+        let n = match ArithmeticCommand::eval_text(inner, core)?.parse::<i64>() {
+            Ok(n) => n,
+            _     => return None,
+        };
+
+        match n < 0 {
+            true  => Some((core.data.get_array_end(name) as i64 + n).to_string()),
+            false => Some(n.to_string()),
+        }
     }
 
     pub fn new() -> Subscript {
@@ -3,44 +3,154 @@
 
 use crate::{ShellCore, Feeder};
 use crate::core::data::Value;
+use crate::elements::command::arithmetic::ArithmeticCommand;
+use std::collections::{BTreeMap, HashMap};
 use super::array::Array;
+use super::subscript::Subscript;
 use super::word::Word;
 
 #[derive(Debug, Clone)]
 pub struct Substitution {
     pub text: String,
     pub key: String,
+    pub index: Option<Subscript>,
     pub value: Value,
 }
 
 impl Substitution {
     pub fn eval(&mut self, core: &mut ShellCore) -> Value {
+        if core.data.is_readonly(&self.key) {
+            eprintln!("sush: {}: readonly variable", &self.key);
+            return Value::None;
+        }
+
         match &self.value {
             Value::None      => Value::EvaluatedSingle("".to_string()),
-            Value::Single(v) => Self::eval_as_value(&v, core),
-            Value::Array(a)  => Self::eval_as_array(&mut a.clone(), core),
+            Value::Single(v) => self.eval_as_value(&v.clone(), core),
+            Value::Array(a)  => {
+                let value = match core.data.is_assoc(&self.key) {
+                    true  => Self::eval_as_assoc(&mut a.clone(), core),
+                    false => Self::eval_as_array(&mut a.clone(), core),
+                };
+                match core.data.get_attributes(&self.key).contains('i') {
+                    true  => Self::eval_elems_as_integer(value, core),
+                    false => value,
+                }
+            },
             _                => Value::None,
         }
     }
 
-    fn eval_as_value(w: &Word, core: &mut ShellCore) -> Value {
-        match w.eval_as_value(core) {
-            Some(s) => Value::EvaluatedSingle(s),
+    fn eval_elems_as_integer(value: Value, core: &mut ShellCore) -> Value {
+        match value {
+            Value::EvaluatedArray(a) => {
+                let mut ans = BTreeMap::new();
+                for (n, v) in a {
+                    match ArithmeticCommand::eval_text(&v, core) {
+                        Some(i) => {ans.insert(n, i);},
+                        None    => return Value::None,
+                    }
+                }
+                Value::EvaluatedArray(ans)
+            },
+            Value::AssocArray(a) => {
+                let mut ans = HashMap::new();
+                for (k, v) in a {
+                    match ArithmeticCommand::eval_text(&v, core) {
+                        Some(i) => {ans.insert(k, i);},
+                        None    => return Value::None,
+                    }
+                }
+                Value::AssocArray(ans)
+            },
+            v => v,
+        }
+    }
+
+    pub fn eval_index(&mut self, core: &mut ShellCore) -> Option<String> {
+        match self.index.as_mut() {
+            Some(sub) => sub.eval(core, &self.key),
+            None      => None,
+        }
+    }
+
+    fn eval_as_value(&self, w: &Word, core: &mut ShellCore) -> Value {
+        let s = match w.eval_as_value(core) {
+            Some(s) => s,
+            None    => return Value::None,
+        };
+
+        if ! core.data.get_attributes(&self.key).contains('i') {
+            return Value::EvaluatedSingle(s);
+        }
+
+        match ArithmeticCommand::eval_text(&s, core) {
+            Some(n) => Value::EvaluatedSingle(n),
             None    => Value::None,
         }
     }
 
+    fn eval_as_assoc(a: &mut Array, core: &mut ShellCore) -> Value {
+        let mut ans = HashMap::new();
+
+        for w in &a.words {
+            let s = match w.eval_for_case_word(core) {
+                Some(s) => s,
+                None    => return Value::None,
+            };
+
+            match (s.starts_with("["), s.find("]=")) {
+                (true, Some(n)) => {ans.insert(s[1..n].to_string(), s[n+2..].to_string());},
+                _ => {
+                    eprintln!("sush: {}: must use subscript when assigning associative array", &s);
+                    return Value::None;
+                },
+            }
+        }
+
+        Value::AssocArray(ans)
+    }
+
     fn eval_as_array(a: &mut Array, core: &mut ShellCore) -> Value {
-        match a.eval(core) {
-            Some(values) => Value::EvaluatedArray(values),
-            None         => Value::None,
+        let mut ans = BTreeMap::new();
+        let mut pos = 0;
+
+        for w in &mut a.words {
+            if w.text.starts_with("[") && w.text.contains("]=") {
+                let s = match w.eval_for_case_word(core) {
+                    Some(s) => s,
+                    None    => return Value::None,
+                };
+                let n = s.find("]=").unwrap();
+                pos = match ArithmeticCommand::eval_text(&s[1..n], core).map(|i| i.parse::<usize>()) {
+                    Some(Ok(i)) => i,
+                    _ => {
+                        eprintln!("sush: {}: bad array subscript", &s);
+                        return Value::None;
+                    },
+                };
+                ans.insert(pos, s[n+2..].to_string());
+                pos += 1;
+                continue;
+            }
+
+            match w.eval(core) {
+                Some(ws) => for v in ws {
+                    ans.insert(pos, v);
+                    pos += 1;
+                },
+                None => return Value::None,
+            }
         }
+
+        Value::EvaluatedArray(ans)
     }
 
     pub fn new() -> Substitution {
         Substitution {
             text: String::new(),
             key: String::new(),
+            index: None,
             value: Value::None,
         }
     }
@@ -58,6 +168,11 @@ impl Substitution {
         name_eq.pop();
         ans.key = name_eq.clone();
 
+        if let Some(n) = name_eq.find("[") {
+            ans.key = name_eq[..n].to_string();
+            ans.index = Subscript::parse(&mut Feeder::new(&name_eq[n..]), core);
+        }
+
         if let Some(a) = Array::parse(feeder, core) {
             ans.text += &a.text;
             ans.value = Value::Array(a);
@@ -7,7 +7,7 @@ mod braced_param;
 mod command;
 mod escaped_char;
 mod ext_glob;
-mod double_quoted;
+pub mod double_quoted;
 pub mod parameter;
 mod varname;
 mod arithmetic;
@@ -16,7 +16,7 @@ use crate::{ShellCore, Feeder};
 use self::arithmetic::Arithmetic;
 use self::simple::SimpleSubword;
 use self::braced_param::BracedParam;
-use self::command::CommandSubstitution;
+use self::command::{CommandSubstitution, ProcessSubstitution};
 use self::escaped_char::EscapedChar;
 use self::ext_glob::ExtGlob;
 use self::double_quoted::DoubleQuoted;
@@ -38,27 +38,43 @@ impl Clone for Box::<dyn Subword> {
     }
 }
 
-fn split_str(s: &str) -> Vec<&str> {
-    let mut esc = false;
-    let mut from = 0;
-    let mut pos = 0;
+pub fn is_ifs_blank(c: char, ifs: &str) -> bool {
+    " \t\n".contains(c) && ifs.contains(c)
+}
+
+/* The flag tells whether an empty field has to be kept as a word.
+   Empty fields appear only on the both sides of a non-blank delimiter. */
+pub fn split_str(s: &str, ifs: &str) -> Vec<(String, bool)> {
+    let chars: Vec<char> = s.chars().collect();
     let mut ans = vec![];
+    let mut field = String::new();
+    let mut pos = 0;
 
-    for c in s.chars() {
-        pos += c.len_utf8();
-        if esc || c == '\\' {
-            esc = ! esc;
-            continue;
+    loop {
+        while pos < chars.len() && ! ifs.contains(chars[pos]) {
+            field.push(chars[pos]);
+            pos += 1;
+        }
+        if pos == chars.len() {
+            ans.push((field, false));
+            return ans;
         }
 
-        if c == ' ' || c == '\t' || c == '\n' {
-            ans.push(&s[from..pos-1]);
-            from = pos;
+        while pos < chars.len() && is_ifs_blank(chars[pos], ifs) {
+            pos += 1;
+        }
+        let mut non_blank = false;
+        if pos < chars.len() && ! is_ifs_blank(chars[pos], ifs) && ifs.contains(chars[pos]) {
+            non_blank = true;
+            pos += 1;
+            while pos < chars.len() && is_ifs_blank(chars[pos], ifs) {
+                pos += 1;
+            }
         }
-    }
 
-    ans.push(&s[from..]);
-    ans
+        ans.push((field, non_blank));
+        field = String::new();
+    }
 }
 
 pub trait Subword {
@@ -68,13 +84,18 @@ pub trait Subword {
     fn substitute(&mut self, _: &mut ShellCore) -> bool {true}
     fn substitute_replace(&self) -> Vec<Box<dyn Subword>> {vec![]}
 
-    fn split(&self, _core: &mut ShellCore) -> Vec<Box<dyn Subword>>{
-        let f = |s| Box::new( SimpleSubword {text: s}) as Box<dyn Subword>;
+    fn split(&self, core: &mut ShellCore) -> Vec<Box<dyn Subword>>{
+        let f = |(s, keep): (String, bool)| match s.is_empty() && keep {
+            true  => Box::new( DoubleQuoted::new() ) as Box<dyn Subword>,
+            false => Box::new( SimpleSubword {text: s}) as Box<dyn Subword>,
+        };
 
-        split_str(self.get_text()).iter().map(|s| f(s.to_string())).collect()
+        let ifs = core.get_ifs();
+        split_str(self.get_text(), &ifs).into_iter().map(f).collect()
     }
 
     fn make_glob_string(&mut self) -> String {self.get_text().to_string()}
+    fn make_regex_string(&mut self) -> String {self.get_text().to_string()}
 
     fn make_unquoted_string(&mut self) -> Option<String> {
         match self.get_text() {
@@ -86,6 +107,7 @@ pub trait Subword {
     fn is_name(&self) -> bool {false}
     fn no_split(&self) -> bool {false}
     fn get_child_subwords(&self) -> Option<&Vec<Box<dyn Subword>>> { None }
+    fn get_elements(&self) -> Option<Vec<String>> { None }
 }
 
 fn replace_history_expansion(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
@@ -122,6 +144,7 @@ pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Box<dyn Subwor
     if let Some(a) = BracedParam::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = Arithmetic::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = CommandSubstitution::parse(feeder, core){ Some(Box::new(a)) }
+    else if let Some(a) = ProcessSubstitution::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = SingleQuoted::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = DoubleQuoted::parse(feeder, core){ Some(Box::new(a)) }
     else if let Some(a) = ExtGlob::parse(feeder, core){ Some(Box::new(a)) }
@@ -1,11 +1,20 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+mod case_conv;
+mod remove;
+mod replace;
+mod substr;
+
 use crate::{ShellCore, Feeder};
 use crate::elements::subword;
 use crate::elements::subword::Subword;
 use crate::elements::subscript::Subscript;
 use crate::elements::word::Word;
+use self::case_conv::CaseConv;
+use self::remove::Remove;
+use self::replace::Replace;
+use self::substr::Substr;
 use super::simple::SimpleSubword;
 
 #[derive(Debug, Clone)]
@@ -16,6 +25,13 @@ pub struct BracedParam {
     pub subscript: Option<Subscript>,
     pub default_symbol: Option<String>,
     pub default_value: Option<Word>,
+    num: bool,
+    indirect: bool,
+    substr: Option<Substr>,
+    remove: Option<Remove>,
+    replace: Option<Replace>,
+    case_conv: Option<CaseConv>,
+    elements: Option<Vec<String>>,
 }
 
 fn is_param(s :&String) -> bool {
@@ -39,38 +55,73 @@ fn is_param(s :&String) -> bool {
     s.chars().position(|c| !name_c(c)) == None
 }
 
+/* byte positions of char boundaries, including the end of the string */
+fn boundaries(s: &str) -> Vec<usize> {
+    let mut ans: Vec<usize> = s.char_indices().map(|e| e.0).collect();
+    ans.push(s.len());
+    ans
+}
+
 impl Subword for BracedParam {
     fn get_text(&self) -> &str { &self.text.as_ref() }
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
 
     fn substitute(&mut self, core: &mut ShellCore) -> bool {
-        if self.name.len() == 0 
+        if self.name.is_empty()
         || ! is_param(&self.name)
-        || ( self.unknown.len() > 0 && ! self.unknown.starts_with("-") ) {
+        || ( ! self.unknown.is_empty() && ! self.unknown.starts_with("-") ) {
             eprintln!("sush: {}: bad substitution", &self.text);
             return false;
         }
 
-        if let Some(sub) = self.subscript.as_mut() {
-            if let Some(s) = sub.eval() {
-                self.text = core.data.get_array(&self.name, &s);
+        if self.indirect {
+            if self.is_array(core) {
+                let keys = core.data.get_array_keys(&self.name);
+                self.text = keys.join(" ");
+                if self.is_at_array(core) {
+                    self.elements = Some(keys);
+                }
+                return true;
+            }
+            self.name = core.data.get_param(&self.name);
+            if ! is_param(&self.name) {
+                eprintln!("sush: {}: invalid indirect expansion", &self.text);
+                return false;
             }
-        }else{
-            let value = core.data.get_param(&self.name);
-            self.text = value.to_string();
         }
 
-        match self.default_symbol.as_ref() {
-            Some(s) => if s == ":+" || self.text == "" {
-                return self.replace_to_default(core);
-            },
-            _ => {},
+        if core.data.flags.contains('u') && self.default_symbol.is_none() {
+            if let Some(name) = self.unbound_name(core) {
+                eprintln!("sush: {}: unbound variable", &name);
+                return false;
+            }
         }
 
+        if self.num {
+            self.text = self.get_length(core).to_string();
+            return true;
+        }
+
+        let values = match self.get_values(core) {
+            Some(vs) => vs,
+            None     => return false,
+        };
+        self.text = values.join(" ");
+        if self.is_at_array(core) {
+            self.elements = Some(values);
+        }
+
+        let alternative = self.default_symbol.as_ref().is_some_and(|s| s.ends_with("+"));
+        if alternative || (self.default_symbol.is_some() && self.is_null_or_unset(core)) {
+            return self.replace_to_default(core);
+        }
+
+        self.default_value = None;
         true
     }
 
     fn set_text(&mut self, text: &str) { self.text = text.to_string(); }
+    fn get_elements(&self) -> Option<Vec<String>> { self.elements.clone() }
 
     fn substitute_replace(&self) -> Vec<Box<dyn Subword>> {
         match self.default_value.as_ref() {
@@ -89,14 +140,144 @@ impl BracedParam {
             subscript: None,
             default_symbol: None,
             default_value: None,
+            num: false,
+            indirect: false,
+            substr: None,
+            remove: None,
+            replace: None,
+            case_conv: None,
+            elements: None,
+        }
+    }
+
+    fn is_array(&mut self, core: &mut ShellCore) -> bool {
+        if self.name == "@" || self.name == "*" {
+            return true;
+        }
+
+        match self.subscript.as_mut() {
+            Some(sub) => matches!(sub.eval(core, &self.name).as_deref(), Some("@") | Some("*")),
+            None      => false,
+        }
+    }
+
+    /* "${a[@]}" and "$@" keep each element as a word */
+    fn is_at_array(&mut self, core: &mut ShellCore) -> bool {
+        if self.name == "@" {
+            return true;
+        }
+
+        match self.subscript.as_mut() {
+            Some(sub) => sub.eval(core, &self.name).as_deref() == Some("@"),
+            None      => false,
+        }
+    }
+
+    fn is_set(&mut self, core: &mut ShellCore) -> bool {
+        core.data.is_set(&self.name)
+    }
+
+    /* for nounset: ${a[@]} may be empty, but ${#a[@]} needs a */
+    fn unbound_name(&mut self, core: &mut ShellCore) -> Option<String> {
+        if self.name == "@" || self.name == "*" {
+            return None;
+        }
+
+        let index = match self.subscript.as_mut() {
+            Some(sub) => sub.eval(core, &self.name),
+            None => return match self.is_set(core) {
+                true  => None,
+                false => Some(self.name.clone()),
+            },
+        };
+
+        match index.as_deref() {
+            Some("@") | Some("*") if ! self.num || self.is_set(core) => None,
+            Some("@") | Some("*") => Some(self.name.clone()),
+            Some(i) if core.data.is_set_elem(&self.name, i) => None,
+            Some(i) => Some(format!("{}[{}]", &self.name, i)),
+            None => None,
+        }
+    }
+
+    fn is_null_or_unset(&mut self, core: &mut ShellCore) -> bool {
+        let colon = self.default_symbol.as_ref().is_some_and(|s| s.starts_with(":"));
+        ! self.is_set(core) || ( colon && self.text.is_empty() )
+    }
+
+    fn get_array(&mut self, core: &mut ShellCore) -> Vec<String> {
+        if self.name == "@" || self.name == "*" {
+            return core.data.get_position_params();
+        }
+
+        let array = core.data.get_array_all(&self.name);
+        match array.len() {
+            0 => match core.data.get_param(&self.name).as_str() {
+                "" => vec![],
+                v  => vec![v.to_string()],
+            },
+            _ => array,
+        }
+    }
+
+    fn get_value(&mut self, core: &mut ShellCore) -> String {
+        if let Some(sub) = self.subscript.as_mut() {
+            match sub.eval(core, &self.name) {
+                Some(s) => core.data.get_array(&self.name, &s),
+                None    => "".to_string(),
+            }
+        }else{
+            core.data.get_param(&self.name)
+        }
+    }
+
+    fn get_length(&mut self, core: &mut ShellCore) -> usize {
+        match self.is_array(core) {
+            true  => self.get_array(core).len(),
+            false => self.get_value(core).chars().count(),
+        }
+    }
+
+    fn get_values(&mut self, core: &mut ShellCore) -> Option<Vec<String>> {
+        let array_mode = self.is_array(core);
+        let mut values = match array_mode {
+            true  => self.get_array(core),
+            false => vec![self.get_value(core)],
+        };
+
+        if let Some(s) = self.substr.as_mut() {
+            values = match array_mode {
+                true  => {
+                    if self.name == "@" || self.name == "*" {
+                        values.insert(0, core.data.get_param("0"));
+                    }
+                    s.get_array(&values, core)?
+                },
+                false => vec![s.get_text(&values[0], core)?],
+            };
+        }
+
+        if let Some(r) = self.remove.as_mut() {
+            values = r.get_texts(&values, core)?;
+        }
+
+        if let Some(r) = self.replace.as_mut() {
+            values = r.get_texts(&values, core)?;
         }
+
+        if let Some(c) = self.case_conv.as_mut() {
+            values = c.get_texts(&values, core)?;
+        }
+
+        Some(values)
     }
 
     fn replace_to_default(&mut self, core: &mut ShellCore) -> bool {
         let symbol = match self.default_symbol.as_ref() {
-            Some(s) => s,
+            Some(s) => s.trim_start_matches(":").to_string(),
             None    => return true,
         };
+        self.elements = None;
 
         let word = match self.default_value.as_ref() {
             Some(w) => match w.tilde_and_dollar_expansion(core) {
@@ -108,25 +289,31 @@ impl BracedParam {
 
         let value: String = word.subwords.iter().map(|s| s.get_text()).collect();
 
-        if symbol == ":-" {
+        if symbol == "-" {
             self.default_value = Some(word);
             return true;
         }
-        if symbol == ":=" {
+        if symbol == "=" {
+            if core.data.is_readonly(&self.name) {
+                eprintln!("sush: {}: readonly variable", &self.name);
+                return false;
+            }
             core.data.set_param(&self.name, &value);
             self.default_value = None;
             self.text = value;
             return true;
         }
-        if symbol == ":?" {
+        if symbol == "?" {
             eprintln!("sush: {}: {}", &self.name, &value);
             return false;
         }
-        if symbol == ":+" {
-            self.default_value = match self.text.as_str() {
-                "" => None,
-                _  => Some(word),
-            };
+        if symbol == "+" {
+            if self.is_null_or_unset(core) {
+                self.default_value = None;
+                return true;
+            }
+            self.text = String::new();
+            self.default_value = Some(word);
             return true;
         }
 
@@ -143,11 +330,44 @@ impl BracedParam {
         false
     }
 
-    fn push_default_subword(len: usize, feeder: &mut Feeder, ans: &mut Self, word: &mut Word) {
-        let blank = feeder.consume(len);
-        let sw = Box::new(SimpleSubword{ text: blank.clone() });
+    fn push_subword(len: usize, feeder: &mut Feeder, text: &mut String, word: &mut Word) {
+        let s = feeder.consume(len);
+        let sw = Box::new(SimpleSubword{ text: s.clone() });
         word.subwords.push(sw);
-        ans.text += &blank.clone();
+        word.text += &s;
+        *text += &s;
+    }
+
+    fn eat_subwords(feeder: &mut Feeder, text: &mut String,
+                    ends: &[&str], core: &mut ShellCore) -> Word {
+        let mut word = Word::new();
+
+        while ! ends.iter().any(|e| feeder.starts_with(e)) {
+            if feeder.len() == 0 {
+                if ! feeder.feed_additional_line(core) {
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(sw) = subword::parse(feeder, core) {
+                *text += sw.get_text();
+                word.text += sw.get_text();
+                word.subwords.push(sw);
+                continue;
+            }
+
+            let len = match feeder.scanner_blank(core) {
+                0 => feeder.scanner_unknown_in_param_brace(),
+                n => n,
+            };
+            if len == 0 {
+                break;
+            }
+            Self::push_subword(len, feeder, text, &mut word);
+        }
+
+        word
     }
 
     fn eat_default_value(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
@@ -161,29 +381,67 @@ impl BracedParam {
 
         let num = feeder.scanner_blank(core);
         ans.text += &feeder.consume(num);
-        let mut word = Word::new();
 
-        while ! feeder.starts_with("}") {
-            if let Some(sw) = subword::parse(feeder, core) {
-                ans.text += sw.get_text();
-                word.text += sw.get_text();
-                word.subwords.push(sw);
-            }
+        ans.default_value = Some(Self::eat_subwords(feeder, &mut ans.text, &["}"], core));
+        true
+    }
 
-            if feeder.starts_with("\n") {
-                Self::push_default_subword(1, feeder, ans, &mut word);
-                feeder.feed_additional_line(core);
-            }
+    fn eat_substr(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        match Substr::parse(feeder, core) {
+            Some(s) => {
+                ans.text += &s.text;
+                ans.substr = Some(s);
+                true
+            },
+            None => false,
+        }
+    }
 
-            let num = feeder.scanner_blank(core);
-            if num != 0 {
-                Self::push_default_subword(num, feeder, ans, &mut word);
-            }
+    fn eat_remove(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        match Remove::parse(feeder, core) {
+            Some(r) => {
+                ans.text += &r.text;
+                ans.remove = Some(r);
+                true
+            },
+            None => false,
         }
+    }
 
-        ans.default_value = Some(word);
+    fn eat_replace(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        match Replace::parse(feeder, core) {
+            Some(r) => {
+                ans.text += &r.text;
+                ans.replace = Some(r);
+                true
+            },
+            None => false,
+        }
+    }
 
-        true
+    fn eat_case_conv(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        match CaseConv::parse(feeder, core) {
+            Some(c) => {
+                ans.text += &c.text;
+                ans.case_conv = Some(c);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn eat_num_sign(feeder: &mut Feeder, ans: &mut Self) {
+        if feeder.starts_with("#") && ! feeder.starts_with("#}") {
+            ans.text += &feeder.consume(1);
+            ans.num = true;
+        }
+    }
+
+    fn eat_indirect_sign(feeder: &mut Feeder, ans: &mut Self) {
+        if feeder.starts_with("!") && ! feeder.starts_with("!}") {
+            ans.text += &feeder.consume(1);
+            ans.indirect = true;
+        }
     }
 
     fn eat_param(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
@@ -226,10 +484,18 @@ impl BracedParam {
         }
         let mut ans = Self::new();
         ans.text += &feeder.consume(2);
+        Self::eat_num_sign(feeder, &mut ans);
+        Self::eat_indirect_sign(feeder, &mut ans);
 
         if Self::eat_param(feeder, &mut ans, core) {
             Self::eat_subscript(feeder, &mut ans, core);
-            Self::eat_default_value(feeder, &mut ans, core);
+            if ! ans.num {
+                let _ = Self::eat_default_value(feeder, &mut ans, core)
+                     || Self::eat_substr(feeder, &mut ans, core)
+                     || Self::eat_remove(feeder, &mut ans, core)
+                     || Self::eat_replace(feeder, &mut ans, core)
+                     || Self::eat_case_conv(feeder, &mut ans, core);
+            }
         }
 
         while ! feeder.starts_with("}") {
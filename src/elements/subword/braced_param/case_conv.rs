@@ -0,0 +1,61 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::word::Word;
+use crate::utils::glob;
+use super::BracedParam;
+
+#[derive(Debug, Clone)]
+pub struct CaseConv {
+    pub text: String,
+    symbol: String,
+    pattern: Word,
+}
+
+impl CaseConv {
+    fn convert(&self, ch: char) -> String {
+        match self.symbol.starts_with("^") {
+            true  => ch.to_uppercase().to_string(),
+            false => ch.to_lowercase().to_string(),
+        }
+    }
+
+    fn conv(&self, s: &str, pattern: &str, extglob: bool) -> String {
+        let mut ans = String::new();
+        for (i, ch) in s.chars().enumerate() {
+            if (i == 0 || self.symbol.len() == 2)
+            && glob::compare(&ch.to_string(), pattern, extglob) {
+                ans += &self.convert(ch);
+            }else{
+                ans.push(ch);
+            }
+        }
+        ans
+    }
+
+    pub fn get_texts(&mut self, texts: &[String], core: &mut ShellCore) -> Option<Vec<String>> {
+        let pattern = match self.pattern.eval_for_case_pattern(core)?.as_str() {
+            "" => "?".to_string(),
+            p  => p.to_string(),
+        };
+        let extglob = core.shopts.query("extglob");
+        Some( texts.iter().map(|s| self.conv(s, &pattern, extglob)).collect() )
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        let len = feeder.scanner_parameter_case_conv_symbol();
+        if len == 0 {
+            return None;
+        }
+
+        let mut ans = CaseConv {
+            text: String::new(),
+            symbol: feeder.consume(len),
+            pattern: Word::new(),
+        };
+        ans.text += &ans.symbol.clone();
+        ans.pattern = BracedParam::eat_subwords(feeder, &mut ans.text, &["}"], core);
+        Some(ans)
+    }
+}
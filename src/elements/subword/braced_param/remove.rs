@@ -0,0 +1,61 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::word::Word;
+use crate::utils::glob;
+use super::{boundaries, BracedParam};
+
+#[derive(Debug, Clone)]
+pub struct Remove {
+    pub text: String,
+    symbol: String,
+    pattern: Word,
+}
+
+impl Remove {
+    fn remove(&self, s: &str, pattern: &str, extglob: bool) -> String {
+        let mut poss = boundaries(s);
+        if self.symbol == "##" || self.symbol == "%" {
+            poss.reverse();
+        }
+
+        for pos in poss {
+            let matched = match self.symbol.starts_with("#") {
+                true  => glob::compare(&s[..pos].to_string(), pattern, extglob),
+                false => glob::compare(&s[pos..].to_string(), pattern, extglob),
+            };
+
+            if matched {
+                return match self.symbol.starts_with("#") {
+                    true  => s[pos..].to_string(),
+                    false => s[..pos].to_string(),
+                };
+            }
+        }
+
+        s.to_string()
+    }
+
+    pub fn get_texts(&mut self, texts: &[String], core: &mut ShellCore) -> Option<Vec<String>> {
+        let pattern = self.pattern.eval_for_case_pattern(core)?;
+        let extglob = core.shopts.query("extglob");
+        Some( texts.iter().map(|s| self.remove(s, &pattern, extglob)).collect() )
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        let len = feeder.scanner_parameter_remove_symbol();
+        if len == 0 {
+            return None;
+        }
+
+        let mut ans = Remove {
+            text: String::new(),
+            symbol: feeder.consume(len),
+            pattern: Word::new(),
+        };
+        ans.text += &ans.symbol.clone();
+        ans.pattern = BracedParam::eat_subwords(feeder, &mut ans.text, &["}"], core);
+        Some(ans)
+    }
+}
@@ -0,0 +1,91 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::word::Word;
+use crate::utils::glob;
+use super::{boundaries, BracedParam};
+
+#[derive(Debug, Clone)]
+pub struct Replace {
+    pub text: String,
+    symbol: String,
+    pattern: Word,
+    replace: Option<Word>,
+}
+
+impl Replace {
+    fn longest_match(&self, s: &str, start: usize, pattern: &str, extglob: bool) -> Option<usize> {
+        if self.symbol == "/%" {
+            return match glob::compare(&s[start..].to_string(), pattern, extglob) {
+                true  => Some(s.len()),
+                false => None,
+            };
+        }
+
+        boundaries(s).into_iter().rev()
+            .filter(|end| *end > start)
+            .find(|end| glob::compare(&s[start..*end].to_string(), pattern, extglob))
+    }
+
+    fn replace(&self, s: &str, pattern: &str, to: &str, extglob: bool) -> String {
+        if pattern.is_empty() {
+            return s.to_string();
+        }
+
+        let mut ans = String::new();
+        let mut start = 0;
+        while start < s.len() {
+            if let Some(end) = self.longest_match(s, start, pattern, extglob) {
+                ans += to;
+                start = end;
+                if self.symbol == "//" {
+                    continue;
+                }
+                break;
+            }
+
+            if self.symbol == "/#" {
+                break;
+            }
+
+            let ch = s[start..].chars().nth(0).unwrap();
+            ans.push(ch);
+            start += ch.len_utf8();
+        }
+
+        ans + &s[start..]
+    }
+
+    pub fn get_texts(&mut self, texts: &[String], core: &mut ShellCore) -> Option<Vec<String>> {
+        let pattern = self.pattern.eval_for_case_pattern(core)?;
+        let to = match self.replace.as_ref() {
+            Some(w) => w.eval_as_value(core)?,
+            None    => "".to_string(),
+        };
+        let extglob = core.shopts.query("extglob");
+        Some( texts.iter().map(|s| self.replace(s, &pattern, &to, extglob)).collect() )
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        let len = feeder.scanner_parameter_replace_symbol();
+        if len == 0 {
+            return None;
+        }
+
+        let mut ans = Replace {
+            text: String::new(),
+            symbol: feeder.consume(len),
+            pattern: Word::new(),
+            replace: None,
+        };
+        ans.text += &ans.symbol.clone();
+        ans.pattern = BracedParam::eat_subwords(feeder, &mut ans.text, &["/", "}"], core);
+
+        if feeder.starts_with("/") {
+            ans.text += &feeder.consume(1);
+            ans.replace = Some(BracedParam::eat_subwords(feeder, &mut ans.text, &["}"], core));
+        }
+        Some(ans)
+    }
+}
@@ -0,0 +1,93 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{ShellCore, Feeder};
+use crate::elements::command::arithmetic::ArithmeticCommand;
+
+#[derive(Debug, Clone)]
+pub struct Substr {
+    pub text: String,
+    offset: String,
+    length: Option<String>,
+}
+
+impl Substr {
+    fn eval_expr(expr: &str, core: &mut ShellCore) -> Option<i64> {
+        if expr.trim() == "" {
+            return Some(0);
+        }
+
+        let ans = ArithmeticCommand::eval_text(expr, core);
+        match ans.as_ref().map(|s| s.parse::<i64>()) {
+            Some(Ok(n)) => Some(n),
+            _ => {
+                eprintln!("sush: {}: syntax error in expression", expr.trim());
+                None
+            },
+        }
+    }
+
+    fn get_range(&mut self, len: usize, is_array: bool,
+                 core: &mut ShellCore) -> Option<(usize, usize)> {
+        let len = len as i64;
+        let mut offset = Self::eval_expr(&self.offset, core)?;
+        if offset < 0 {
+            offset += len;
+        }
+        if offset < 0 || offset > len {
+            return Some((0, 0));
+        }
+
+        let length = match self.length.clone() {
+            Some(s) => Self::eval_expr(&s, core)?,
+            None    => len - offset,
+        };
+
+        let end = match length < 0 {
+            true  => length + len,
+            false => length + offset,
+        };
+        if end < offset || (is_array && length < 0) {
+            eprintln!("sush: {}: substring expression < 0", length);
+            return None;
+        }
+
+        Some((offset as usize, std::cmp::min(end, len) as usize))
+    }
+
+    pub fn get_text(&mut self, text: &str, core: &mut ShellCore) -> Option<String> {
+        let (from, to) = self.get_range(text.chars().count(), false, core)?;
+        Some(text.chars().skip(from).take(to - from).collect())
+    }
+
+    pub fn get_array(&mut self, array: &[String], core: &mut ShellCore) -> Option<Vec<String>> {
+        let (from, to) = self.get_range(array.len(), true, core)?;
+        Some(array[from..to].to_vec())
+    }
+
+    pub fn parse(feeder: &mut Feeder, _: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with(":") {
+            return None;
+        }
+
+        let mut ans = Substr {
+            text: feeder.consume(1),
+            offset: String::new(),
+            length: None,
+        };
+
+        let len = feeder.scanner_substr_expr();
+        ans.offset = feeder.consume(len);
+        ans.text += &ans.offset;
+
+        if feeder.starts_with(":") {
+            ans.text += &feeder.consume(1);
+            let len = feeder.scanner_substr_expr();
+            let length = feeder.consume(len);
+            ans.text += &length;
+            ans.length = Some(length);
+        }
+
+        Some(ans)
+    }
+}
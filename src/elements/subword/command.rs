@@ -6,11 +6,11 @@ use crate::elements::Pipe;
 use crate::elements::command::Command;
 use crate::elements::command::paren::ParenCommand;
 use crate::elements::subword::Subword;
-use nix::unistd;
+use nix::{fcntl, unistd};
 use std::{thread, time};
 use std::fs::File;
 use std::io::{BufReader, BufRead, Error};
-use std::os::fd::{FromRawFd, RawFd};
+use std::os::fd::{FromRawFd, IntoRawFd, RawFd};
 use std::sync::atomic::Ordering::Relaxed;
 
 #[derive(Debug, Clone)]
@@ -26,9 +26,17 @@ impl Subword for CommandSubstitution {
     fn substitute(&mut self, core: &mut ShellCore) -> bool {
         let mut pipe = Pipe::new("|".to_string());
         pipe.set(-1, unistd::getpgrp());
+        let flags = core.data.flags.clone();
+        if ! core.shopts.query("inherit_errexit") {
+            core.data.flags.retain(|f| f != 'e');
+        }
         let pid = self.command.exec(core, &mut pipe);
+        core.data.flags = flags;
+
         let result = self.read(pipe.recv, core);
-        core.wait_pipeline(vec![pid], false, false);
+        let exempted = core.errexit_exempted;
+        core.exec_ignoring_errexit(|c| {c.wait_pipeline(vec![pid], false, false);});
+        core.errexit_exempted = exempted;
         result
     }
 }
@@ -85,3 +93,65 @@ impl CommandSubstitution {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct ProcessSubstitution {
+    pub text: String,
+    direction: char,
+    command: ParenCommand,
+}
+
+impl Subword for ProcessSubstitution {
+    fn get_text(&self) -> &str {self.text.as_ref()}
+    fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
+
+    fn substitute(&mut self, core: &mut ShellCore) -> bool {
+        let mut pipe = Pipe::new("|".to_string());
+        let fd = match self.direction {
+            '<' => {
+                pipe.set(-1, unistd::getpgrp());
+                pipe.recv
+            },
+            _ => {
+                /* the read end becomes the stdin of the child
+                   and the write end is closed in the child as recv */
+                let (recv, send) = unistd::pipe().expect("Cannot open pipe");
+                pipe.prev = recv.into_raw_fd();
+                pipe.recv = send.into_raw_fd();
+                pipe.pgid = unistd::getpgrp();
+                pipe.recv
+            },
+        };
+
+        let pid = self.command.exec(core, &mut pipe);
+
+        let fd = match fcntl::fcntl(fd, fcntl::F_DUPFD(60)) {
+            Ok(newfd) => {
+                let _ = unistd::close(fd);
+                newfd
+            },
+            Err(_) => fd,
+        };
+
+        core.proc_substs.push((fd, pid));
+        self.text = format!("/dev/fd/{}", fd);
+        true
+    }
+}
+
+impl ProcessSubstitution {
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
+        if ! feeder.starts_with("<(") && ! feeder.starts_with(">(") {
+            return None;
+        }
+        let mut text = feeder.consume(1);
+        let direction = text.chars().next().unwrap();
+
+        if let Some(pc) = ParenCommand::parse(feeder, core, true) {
+            text += &pc.get_text();
+            Some(ProcessSubstitution {text, direction, command: pc} )
+        }else{
+            None
+        }
+    }
+}
@@ -4,13 +4,15 @@
 use crate::{error_message, ShellCore, Feeder};
 use crate::elements::word::{Word, substitution};
 use crate::elements::subword::CommandSubstitution;
-use super::{BracedParam, EscapedChar, SimpleSubword, Parameter, Subword, VarName};
+use crate::utils::regex;
+use super::{Arithmetic, BracedParam, EscapedChar, SimpleSubword, Parameter, Subword, VarName};
 
 #[derive(Debug, Clone)]
 pub struct DoubleQuoted {
     text: String,
     subwords: Vec<Box<dyn Subword>>,
     split_points: Vec<usize>,
+    no_word: bool,
 }
 
 impl Subword for DoubleQuoted {
@@ -24,7 +26,14 @@ impl Subword for DoubleQuoted {
             return false;
         }
         self.subwords = word.subwords;
-        self.text = self.subwords.iter().map(|s| s.get_text()).collect();
+        self.split_arrays();
+        self.text = self.subwords.iter()
+            .enumerate()
+            .map(|(i, s)| match self.split_points.contains(&i) {
+                true  => " ".to_string() + s.get_text(),
+                false => s.get_text().to_string(),
+            })
+            .collect();
         true
     }
 
@@ -36,20 +45,22 @@ impl Subword for DoubleQuoted {
                         .replace("]", "\\]");
     }
 
+    fn make_regex_string(&mut self) -> String {
+        regex::escape(&self.text)
+    }
+
     fn make_unquoted_string(&mut self) -> Option<String> {
-        Some(self.subwords.iter_mut()
-            .map(|s| s.make_unquoted_string())
-            .filter(|s| *s != None)
-            .map(|s| s.unwrap())
-            .collect::<Vec<String>>()
-            .concat() )
+        if self.no_word && self.subwords.is_empty() {
+            return None;
+        }
+        Some(self.joined_texts().concat())
     }
 
     fn split(&self, _core: &mut ShellCore) -> Vec<Box<dyn Subword>>{
         let mut ans = vec![];
 
         let mut points = self.split_points.clone();
-        points[self.split_points.len()-1] = self.split_points.len();
+        points.push(self.subwords.len());
 
         let mut last = 0;
         for p in points {
@@ -62,7 +73,7 @@ impl Subword for DoubleQuoted {
         ans
     }
 
-    fn no_split(&self) -> bool {self.split_points.len() < 2}
+    fn no_split(&self) -> bool {self.split_points.is_empty()}
 }
 
 impl DoubleQuoted {
@@ -71,6 +82,7 @@ impl DoubleQuoted {
             text: String::new(),
             subwords: vec![],
             split_points: vec![],
+            no_word: false,
         }
     }
 
@@ -79,10 +91,13 @@ impl DoubleQuoted {
 
         for sw in &self.subwords {
             if sw.get_text() == "$@" {
-                for pp in core.data.get_position_params() {
+                for (i, pp) in core.data.get_position_params().into_iter().enumerate() {
+                    if i > 0 {
+                        self.split_points.push(ans.len());
+                    }
                     ans.push(Box::new( SimpleSubword {text: pp}) as Box<dyn Subword>);
-                    self.split_points.push(ans.len());
                 }
+                self.no_word = true;
             }else{
                 ans.push(sw.boxed_clone());
             }
@@ -90,6 +105,46 @@ impl DoubleQuoted {
         ans
     }
 
+    /* split_points hold the positions where a new word begins */
+    fn split_arrays(&mut self) {
+        let mut ans = vec![];
+        let mut points = vec![];
+
+        for (i, sw) in self.subwords.iter().enumerate() {
+            if self.split_points.contains(&i) {
+                points.push(ans.len());
+            }
+
+            match sw.get_elements() {
+                Some(elems) => {
+                    for (j, e) in elems.into_iter().enumerate() {
+                        if j > 0 {
+                            points.push(ans.len());
+                        }
+                        ans.push(Box::new( SimpleSubword {text: e}) as Box<dyn Subword>);
+                    }
+                    self.no_word = true;
+                },
+                None => ans.push(sw.clone()),
+            }
+        }
+
+        self.subwords = ans;
+        self.split_points = points;
+    }
+
+    /* elements left unsplit (e.g. in assignments) are joined with spaces */
+    fn joined_texts(&mut self) -> Vec<String> {
+        let points = self.split_points.clone();
+        self.subwords.iter_mut()
+            .enumerate()
+            .map(|(i, s)| match points.contains(&i) {
+                true  => " ".to_string() + &s.make_unquoted_string().unwrap_or_default(),
+                false => s.make_unquoted_string().unwrap_or_default(),
+            })
+            .collect()
+    }
+
     fn set_simple_subword(feeder: &mut Feeder, ans: &mut Self, len: usize) -> bool {
         if len == 0 {
             return false;
@@ -111,6 +166,16 @@ impl DoubleQuoted {
         }
     }
 
+    fn eat_arithmetic(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        if let Some(a) = Arithmetic::parse(feeder, core){
+            ans.text += a.get_text();
+            ans.subwords.push(Box::new(a));
+            true
+        }else{
+            false
+        }
+    }
+
     fn eat_command_substitution(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
         if let Some(a) = CommandSubstitution::parse(feeder, core){
             ans.text += a.get_text();
@@ -139,7 +204,7 @@ impl DoubleQuoted {
     }
 
     fn eat_escaped_char(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
-        if feeder.starts_with("\\$") || feeder.starts_with("\\\\") {
+        if feeder.starts_with("\\$") || feeder.starts_with("\\\\") || feeder.starts_with("\\`") {
             let txt = feeder.consume(2);
             ans.text += &txt;
             ans.subwords.push(Box::new(EscapedChar{ text: txt }));
@@ -166,6 +231,27 @@ impl DoubleQuoted {
         Self::set_simple_subword(feeder, ans, len)
     }
 
+    pub fn parse_here_document(feeder: &mut Feeder, core: &mut ShellCore) -> DoubleQuoted {
+        let mut ans = Self::new();
+
+        while feeder.len() > 0 {
+            while Self::eat_braced_param(feeder, &mut ans, core)
+               || Self::eat_arithmetic(feeder, &mut ans, core)
+               || Self::eat_command_substitution(feeder, &mut ans, core)
+               || Self::eat_special_or_positional_param(feeder, &mut ans, core)
+               || Self::eat_doller(feeder, &mut ans)
+               || Self::eat_escaped_char(feeder, &mut ans, core)
+               || Self::eat_name(feeder, &mut ans, core)
+               || Self::eat_other(feeder, &mut ans, core) {}
+
+            if feeder.starts_with("\"") { //a double quote is an ordinary char in here-documents
+                Self::set_simple_subword(feeder, &mut ans, 1);
+            }
+        }
+
+        ans
+    }
+
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<DoubleQuoted> {
         if ! feeder.starts_with("\"") {
             return None;
@@ -175,6 +261,7 @@ impl DoubleQuoted {
 
         loop {
             while Self::eat_braced_param(feeder, &mut ans, core)
+               || Self::eat_arithmetic(feeder, &mut ans, core)
                || Self::eat_command_substitution(feeder, &mut ans, core)
                || Self::eat_special_or_positional_param(feeder, &mut ans, core)
                || Self::eat_doller(feeder, &mut ans)
@@ -3,6 +3,7 @@
 
 use crate::{error_message, ShellCore, Feeder};
 use crate::elements::subword::Subword;
+use crate::utils::regex;
 
 #[derive(Debug, Clone)]
 pub struct EscapedChar {
@@ -12,6 +13,7 @@ pub struct EscapedChar {
 impl Subword for EscapedChar {
     fn get_text(&self) -> &str {&self.text.as_ref()}
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
+    fn no_split(&self) -> bool {true}
 
     fn make_unquoted_string(&mut self) -> Option<String> {
         match self.text.len() {
@@ -20,6 +22,10 @@ impl Subword for EscapedChar {
             _ => Some(self.text[1..].to_string()),
         }
     }
+
+    fn make_regex_string(&mut self) -> String {
+        regex::escape(&self.text[1..])
+    }
 }
 
 impl EscapedChar {
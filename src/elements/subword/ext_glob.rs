@@ -15,6 +15,7 @@ impl Subword for ExtGlob {
     fn get_text(&self) -> &str {&self.text.as_ref()}
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
     fn get_child_subwords(&self) -> Option<&Vec<Box<dyn Subword>>> { Some(&self.subwords) }
+    fn no_split(&self) -> bool {true}
 }
 
 impl ExtGlob {
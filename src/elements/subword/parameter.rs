@@ -14,7 +14,18 @@ impl Subword for Parameter {
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
 
     fn substitute(&mut self, core: &mut ShellCore) -> bool {
-        let value = core.data.get_param(&self.text[1..]);
+        let name = &self.text[1..];
+        if core.data.flags.contains('u') && name != "@" && name != "*"
+        && ! core.data.is_set(name) {
+            let shown = match name.parse::<usize>() {
+                Ok(_) => &self.text,
+                _     => name,
+            };
+            eprintln!("sush: {}: unbound variable", shown);
+            return false;
+        }
+
+        let value = core.data.get_param(name);
         self.text = value.to_string();
         true
     }
@@ -13,6 +13,9 @@ impl Subword for SimpleSubword {
     fn get_text(&self) -> &str {&self.text.as_ref()}
     fn set_text(&mut self, text: &str) { self.text = text.to_string(); }
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
+
+    /* literal text is not split. blanks are found here only in ${name-word} */
+    fn no_split(&self) -> bool { ! self.text.chars().all(|c| " \t\n".contains(c)) }
 }
 
 impl SimpleSubword {
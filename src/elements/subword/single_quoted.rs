@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::utils::regex;
 use super::Subword;
 
 #[derive(Debug, Clone)]
@@ -25,6 +26,10 @@ impl Subword for SingleQuoted {
             .replace("]", "\\]")
     }
 
+    fn make_regex_string(&mut self) -> String {
+        regex::escape(&self.text[1..self.text.len()-1])
+    }
+
     fn no_split(&self) -> bool {true}
 }
 
@@ -14,6 +14,7 @@ impl Subword for VarName {
     fn set_text(&mut self, text: &str) { self.text = text.to_string(); }
     fn boxed_clone(&self) -> Box<dyn Subword> {Box::new(self.clone())}
     fn is_name(&self) -> bool {true}
+    fn no_split(&self) -> bool {true}
 }
 
 impl VarName {
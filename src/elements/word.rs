@@ -22,7 +22,7 @@ impl Word {
         let mut ws = vec![];
         for w in brace_expansion::eval(&mut self.clone()) {
             match w.tilde_and_dollar_expansion(core) {
-                Some(w) => ws.append( &mut w.split_and_path_expansion(core) ),
+                Some(w) => ws.append( &mut w.split_and_path_expansion(core)? ),
                 None    => return None,
             };
         }
@@ -31,12 +31,8 @@ impl Word {
     }
 
     pub fn eval_as_value(&self, core: &mut ShellCore) -> Option<String> {
-        let mut ws = match self.tilde_and_dollar_expansion(core) {
-            Some(w) => w.split_and_path_expansion(core),
-            None    => return None,
-        };
-
-        Some( Self::make_args(&mut ws).join(" ") )
+        self.tilde_and_dollar_expansion(core)
+            .map(|mut w| w.make_unquoted_word().unwrap_or_default())
     }
 
     pub fn eval_for_case_word(&self, core: &mut ShellCore) -> Option<String> {
@@ -53,6 +49,11 @@ impl Word {
         }
     }
 
+    pub fn eval_for_regex(&self, core: &mut ShellCore) -> Option<String> {
+        self.tilde_and_dollar_expansion(core)
+            .map(|mut w| w.make_regex_string())
+    }
+
     pub fn tilde_and_dollar_expansion(&self, core: &mut ShellCore) -> Option<Word> {
         let mut w = self.clone();
         tilde_expansion::eval(&mut w, core);
@@ -62,13 +63,12 @@ impl Word {
         }
     }
 
-    pub fn split_and_path_expansion(&self, core: &mut ShellCore) -> Vec<Word> {
+    pub fn split_and_path_expansion(&self, core: &mut ShellCore) -> Option<Vec<Word>> {
         let mut ans = vec![];
-        let extglob = core.shopts.query("extglob");
         for mut w in split::eval(self, core) {
-            ans.append(&mut path_expansion::eval(&mut w, extglob) );
+            ans.append(&mut path_expansion::eval(&mut w, core)? );
         }
-        ans
+        Some(ans)
     }
 
     fn make_args(words: &mut Vec<Word>) -> Vec<String> {
@@ -99,6 +99,13 @@ impl Word {
             .concat()
     }
 
+    fn make_regex_string(&mut self) -> String {
+        self.subwords.iter_mut()
+            .map(|s| s.make_regex_string())
+            .collect::<Vec<String>>()
+            .concat()
+    }
+
     fn scan_pos(&self, s: &str) -> Vec<usize> {
         self.subwords.iter()
             .enumerate()
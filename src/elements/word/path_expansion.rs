@@ -1,49 +1,129 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use crate::ShellCore;
 use crate::elements::word::Word;
-use crate::utils::directory;
+use crate::utils::{directory, glob};
 use super::subword::simple::SimpleSubword;
+use std::path::Path;
 
-pub fn eval(word: &mut Word, extglob: bool) -> Vec<Word> {
-    let paths = expand(&word.make_glob_string(), extglob);
+struct GlobOptions {
+    extglob: bool,
+    dotglob: bool,
+    globstar: bool,
+    nocase: bool,
+    ignores: Vec<String>,
+}
+
+impl GlobOptions {
+    fn new(core: &mut ShellCore) -> Self {
+        let globignore = core.data.get_param("GLOBIGNORE");
+        GlobOptions {
+            extglob: core.shopts.query("extglob"),
+            dotglob: core.shopts.query("dotglob") || ! globignore.is_empty(),
+            globstar: core.shopts.query("globstar"),
+            nocase: core.shopts.query("nocaseglob"),
+            ignores: globignore.split(":").filter(|p| ! p.is_empty())
+                               .map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+/* only for command words, redirection targets and word lists of for, select and arrays */
+pub fn eval(word: &mut Word, core: &mut ShellCore) -> Option<Vec<Word>> {
+    let globstr = word.make_glob_string();
+    if core.data.flags.contains('f') || ! is_glob(&globstr) {
+        return Some(vec![word.clone()]);
+    }
+
+    let paths = expand(&globstr, &GlobOptions::new(core));
 
     if paths.len() > 0 {
         let mut tmp = word.clone();
-        paths.iter()
-             .map(|p| rewrite(&mut tmp, &p))
-             .collect()
+        Some( paths.iter().map(|p| rewrite(&mut tmp, &p)).collect() )
+    }else if core.shopts.query("failglob") {
+        eprintln!("sush: no match: {}", &word.text);
+        core.word_eval_error = true; //failglob aborts the whole command line
+        None
+    }else if core.shopts.query("nullglob") {
+        Some(vec![])
     }else{
-        vec![word.clone()]
+        Some(vec![word.clone()])
     }
 }
 
-fn expand(globstr: &str, extglob: bool) -> Vec<String> {
-    if globstr.find("*") == None 
-    && globstr.find("?") == None
-    && globstr.find("@") == None
-    && globstr.find("+") == None
-    && globstr.find("!") == None
-    && globstr.find("[") == None {
-        return vec![];
+/* quoted characters are escaped with backslashes in globstr */
+fn is_glob(globstr: &str) -> bool {
+    let mut chars = globstr.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => { chars.next(); },
+            '*' | '?' | '[' => return true,
+            '@' | '+' | '!' if chars.peek() == Some(&'(') => return true,
+            _ => {},
+        }
     }
-        
+    false
+}
+
+fn expand(globstr: &str, opts: &GlobOptions) -> Vec<String> {
     let mut ans_cands = vec!["".to_string()];
     let mut tmp_ans_cands = vec![];
 
-    for glob_elem in globstr.split("/") {
+    let elems = globstr.split("/").collect::<Vec<&str>>();
+    for (i, glob_elem) in elems.iter().enumerate() {
         for cand in ans_cands {
-            tmp_ans_cands.extend( directory::glob(&cand, &glob_elem, extglob) );
+            if opts.globstar && *glob_elem == "**" {
+                match i == elems.len() - 1 {
+                    true  => tmp_ans_cands.push(cand.clone() + "/"),
+                    false => tmp_ans_cands.push(cand.clone()),
+                }
+                tmp_ans_cands.extend( descendants(&cand, opts.dotglob) );
+                continue;
+            }
+            tmp_ans_cands.extend( directory::glob(&cand, &glob_elem,
+                                  opts.extglob, opts.dotglob, opts.nocase) );
         }
         ans_cands = tmp_ans_cands.clone();
         tmp_ans_cands.clear();
     }
 
+    if globstr.ends_with("/") {
+        ans_cands.retain(|e| Path::new(e).is_dir());
+    }
     ans_cands.iter_mut().for_each(|e| {e.pop();} );
+    ans_cands.retain(|e| ! e.is_empty() && ! is_ignored(e, opts));
     ans_cands.sort();
+    ans_cands.dedup();
     ans_cands
 }
 
+/* all files and directories under dir for ** of globstar */
+fn descendants(dir: &str, dotglob: bool) -> Vec<String> {
+    let mut ans = vec![];
+    for f in directory::files(dir) {
+        if f.starts_with(".") && ! dotglob {
+            continue;
+        }
+        let path = dir.to_owned() + &f + "/";
+        ans.push(path.clone());
+        if directory::is_real_dir(&path) {
+            ans.extend( descendants(&path, dotglob) );
+        }
+    }
+    ans
+}
+
+fn is_ignored(path: &str, opts: &GlobOptions) -> bool {
+    if opts.ignores.is_empty() {
+        return false;
+    }
+
+    let file = path.rsplit("/").next().unwrap_or(path);
+    file == "." || file == ".."
+    || opts.ignores.iter().any(|p| glob::compare(&path.to_string(), p, opts.extglob))
+}
+
 fn rewrite(word: &mut Word, path: &str) -> Word {
     word.subwords[0] = Box::new( SimpleSubword{ text: path.to_string() } );
     while word.subwords.len() > 1 {
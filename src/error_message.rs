@@ -35,6 +35,10 @@ pub fn assignment(right: &str) -> String {
     format!("attempted assignment to non-variable (error token is \"{}\")", right)
 }
 
+pub fn readonly(name: &str) -> String {
+    format!("{}: readonly variable", name)
+}
+
 pub fn syntax(token: &str) -> String {
     format!("{0}: syntax error: operand expected (error token is \"{0}\")", token)
 }
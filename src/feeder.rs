@@ -149,6 +149,49 @@ impl Feeder {
         };
     }
 
+    fn is_end_of_here_document(line: &str, end_mark: &str) -> bool {
+        line == end_mark || line == end_mark.to_owned() + "\n"
+    }
+
+    pub fn cut_here_document(&mut self, end_mark: &str, remove_tab: bool,
+                             core: &mut ShellCore) -> Option<String> {
+        let start = loop {
+            match self.remaining.find('\n') {
+                Some(n) => break n + 1,
+                None    => if ! self.feed_additional_line(core) { return None; },
+            }
+        };
+
+        let mut body = String::new();
+        let mut pos = start;
+        loop {
+            let line_len = match self.remaining[pos..].find('\n') {
+                Some(n) => n + 1,
+                None    => self.remaining.len() - pos,
+            };
+
+            let mut line = &self.remaining[pos..pos+line_len];
+            if remove_tab {
+                line = line.trim_start_matches('\t');
+            }
+
+            if Self::is_end_of_here_document(line, end_mark) {
+                pos += line_len;
+                break;
+            }
+
+            if line.ends_with("\n") {
+                body += line;
+                pos += line_len;
+            }else if ! self.feed_additional_line(core) {
+                return None;
+            }
+        }
+
+        self.remaining = self.remaining[..start].to_string() + &self.remaining[pos..];
+        Some(body)
+    }
+
     pub fn replace(&mut self, num: usize, to: &str) {
         self.consume(num);
         self.remaining = to.to_string() + &self.remaining;
@@ -50,6 +50,47 @@ impl Feeder {
                               "@", "!", "+", "-", ".", ":", "=", "^", ","])
     }
 
+    pub fn scanner_regex_symbol(&self, in_paren: bool) -> usize {
+        match in_paren {
+            true  => self.scanner_one_of(&["(", ")", "|", " ", "\t"]),
+            false => self.scanner_one_of(&["(", "|"]),
+        }
+    }
+
+    pub fn scanner_regex_bracket(&self) -> usize {
+        if ! self.starts_with("[") {
+            return 0;
+        }
+
+        let chars: Vec<char> = self.remaining.chars().collect();
+        let mut pos = 1;
+        if chars.get(pos) == Some(&'^') {
+            pos += 1;
+        }
+        if chars.get(pos) == Some(&']') {
+            pos += 1;
+        }
+
+        while pos < chars.len() {
+            match (chars[pos], chars.get(pos+1)) {
+                (']', _) => {
+                    return chars[..pos+1].iter().map(|c| c.len_utf8()).sum();
+                },
+                ('[', Some(d)) if ":.=".contains(*d) => {
+                    let close = format!("{}]", d);
+                    let rest: String = chars[pos+2..].iter().collect();
+                    match rest.find(&close) {
+                        Some(n) => pos += 2 + rest[..n].chars().count() + 2,
+                        None    => return 0,
+                    }
+                },
+                (' ', _) | ('\t', _) | ('\n', _) => return 0,
+                _ => pos += 1,
+            }
+        }
+        0
+    }
+
     pub fn scanner_math_symbol(&mut self, core: &mut ShellCore) -> usize {
         self.backslash_check_and_feed(vec![""], core);
         self.scanner_one_of(&["/", "*", "?", ":", "+", "-", "=", "^", "%", ","])
@@ -229,8 +270,16 @@ impl Feeder {
             return 0;
         }
 
-        if self.remaining.chars().nth(name_len).unwrap_or('x') == '=' {
-            name_len + 1
+        let mut len = name_len;
+        if self.remaining[len..].starts_with("[") {
+            match self.remaining[len..].find("]") {
+                Some(n) => len += n + 1,
+                None    => return 0,
+            }
+        }
+
+        if self.remaining[len..].starts_with("=") {
+            len + 1
         }else{
             0
         }
@@ -270,11 +319,44 @@ impl Feeder {
 
     pub fn scanner_redirect_symbol(&mut self, core: &mut ShellCore) -> usize {
         self.backslash_check_and_feed(vec![">", "&"], core);
-        self.scanner_one_of(&["&>", ">&", ">>", "<", ">"])
+        if self.starts_with("<(") || self.starts_with(">(") { //process substitution
+            return 0;
+        }
+        self.scanner_one_of(&["&>>", "&>", ">&", ">>", ">|", "<<<", "<<-", "<<", "<&", "<>", "<", ">"])
     }
 
     pub fn scanner_parameter_default_symbol(&mut self) -> usize {
-        self.scanner_one_of(&[":-", ":=", ":?", ":+"])
+        self.scanner_one_of(&[":-", ":=", ":?", ":+", "-", "=", "?", "+"])
+    }
+
+    pub fn scanner_parameter_remove_symbol(&mut self) -> usize {
+        self.scanner_one_of(&["##", "#", "%%", "%"])
+    }
+
+    pub fn scanner_parameter_replace_symbol(&mut self) -> usize {
+        self.scanner_one_of(&["//", "/#", "/%", "/"])
+    }
+
+    pub fn scanner_parameter_case_conv_symbol(&mut self) -> usize {
+        self.scanner_one_of(&["^^", "^", ",,", ","])
+    }
+
+    pub fn scanner_substr_expr(&mut self) -> usize {
+        let mut ans = 0;
+        let mut nest = 0;
+        for ch in self.remaining.chars() {
+            if nest == 0 && (ch == ':' || ch == '}') {
+                break;
+            }
+
+            match ch {
+                '(' | '[' | '{' => nest += 1,
+                ')' | ']' | '}' => nest -= 1,
+                _ => {},
+            }
+            ans += ch.len_utf8();
+        }
+        ans
     }
 
     pub fn scanner_test_check_option(&mut self, core: &mut ShellCore) -> usize {
@@ -295,7 +377,7 @@ impl Feeder {
 
     pub fn scanner_test_compare_op(&mut self, core: &mut ShellCore) -> usize {
         self.backslash_check_and_feed(vec!["-", "-e", "-n", "-o", "=", "!"], core);
-        self.scanner_one_of(&["-ef", "-nt", "-ot", "==", "=", "!=", "<", ">",
+        self.scanner_one_of(&["-ef", "-nt", "-ot", "=~", "==", "=", "!=", "<", ">",
                               "-eq", "-ne", "-lt", "-le", "-gt", "-ge"])
     }
 }
@@ -133,18 +133,34 @@ fn set_history(core: &mut ShellCore, s: &str) {
     }
 }
 
+fn ignore_eof(core: &mut ShellCore, eof_count: &mut usize) -> bool {
+    if ! core.data.flags.contains('i') || ! core.options.query("ignoreeof") {
+        return false;
+    }
+
+    let max = core.data.get_param("IGNOREEOF").parse::<usize>().unwrap_or(10);
+    *eof_count += 1;
+    if *eof_count > max {
+        return false;
+    }
+    eprintln!("Use \"exit\" to leave the shell.");
+    true
+}
+
 fn main_loop(core: &mut ShellCore) {
     let mut feeder = Feeder::new("");
+    let mut eof_count = 0;
     loop {
         core.jobtable_check_status();
         core.jobtable_print_status_change();
 
         match feeder.feed_line(core) {
-            Ok(()) => {}, 
+            Ok(()) => eof_count = 0,
             Err(InputError::Interrupt) => {
                 signal::input_interrupt_check(&mut feeder, core);
                 continue;
             },
+            Err(InputError::Eof) if ignore_eof(core, &mut eof_count) => continue,
             _ => break,
         }
 
@@ -152,7 +168,9 @@ fn main_loop(core: &mut ShellCore) {
         core.sigint.store(false, Relaxed);
         match Script::parse(&mut feeder, core, false){
             Some(mut s) => {
+                let exit_warned = core.exit_warned;
                 s.exec(core);
+                core.exit_warned &= ! exit_warned; //only until the next command
                 set_history(core, &s.get_text());
             },
             None => {},
@@ -4,10 +4,11 @@
 pub mod file_check;
 pub mod glob;
 pub mod directory;
+pub mod regex;
 
 pub fn reserved(w: &str) -> bool {
     match w {
-        "[[" | "]]" | "{" | "}" | "while" | "for" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" | "case" => true,
+        "[[" | "]]" | "{" | "}" | "while" | "until" | "for" | "select" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" | "case" | "coproc" => true,
         _ => false,
     }
 }
@@ -19,7 +19,11 @@ pub fn files(dir: &str) -> Vec<String> {
     }
 }
 
-pub fn glob(dir: &str, glob: &str, extglob: bool) -> Vec<String> {
+pub fn is_real_dir(path: &str) -> bool {
+    Path::new(path).symlink_metadata().is_ok_and(|m| m.is_dir())
+}
+
+pub fn glob(dir: &str, glob: &str, extglob: bool, dotglob: bool, nocase: bool) -> Vec<String> {
     let make_path = |file| dir.to_owned() + file + "/";
 
     if glob == "" || glob == "." || glob == ".." {
@@ -29,8 +33,59 @@ pub fn glob(dir: &str, glob: &str, extglob: bool) -> Vec<String> {
     let mut fs = files(dir);
     fs.append( &mut vec![".".to_string(), "..".to_string()] );
 
-    let compare = |file: &String| ( ! file.starts_with(".") || glob.starts_with(".") )
-                            && glob::compare(file, glob, extglob);
+    let hidden_ok = |file: &String| ! file.starts_with(".") || glob.starts_with(".")
+                            || ( dotglob && file != "." && file != ".." );
+    let compare = |file: &String| hidden_ok(file) && match nocase {
+        true  => glob::compare_nocase(file, glob, extglob),
+        false => glob::compare(file, glob, extglob),
+    };
 
     fs.iter().filter(|f| compare(f) ).map(|f| make_path(f) ).collect()
 }
+
+/* 0: same, 1: two chars are transposed, 2: one char is wrong, missing or extra */
+fn spell_distance(a: &str, b: &str) -> usize {
+    if a == b {
+        return 0;
+    }
+
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let same = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[same..], &b[same..]);
+
+    if a.len() >= 2 && b.len() >= 2
+    && a[0] == b[1] && a[1] == b[0] && a[2..] == b[2..] {
+        1
+    }else if a.len() == b.len() && a[1..] == b[1..] 
+    || a.len() + 1 == b.len() && a == &b[1..]
+    || a.len() == b.len() + 1 && &a[1..] == b {
+        2
+    }else{
+        3
+    }
+}
+
+/* for cdspell and dirspell */
+pub fn correct_spelling(path: &str) -> Option<String> {
+    let mut dir = match path.starts_with("/") {
+        true  => "/".to_string(),
+        false => "".to_string(),
+    };
+
+    for name in path.split("/").filter(|n| ! n.is_empty()) {
+        let ok = |n: &str| Path::new(&(dir.clone() + n)).is_dir();
+        let name = match name == "." || name == ".." || ok(name) {
+            true  => name.to_string(),
+            false => files(&dir).into_iter()
+                     .filter(|f| ok(f))
+                     .map(|f| (spell_distance(name, &f), f))
+                     .filter(|(d, _)| *d <= 2)
+                     .min()?.1,
+        };
+        dir += &(name + "/");
+    }
+
+    dir.pop();
+    Some(dir)
+}
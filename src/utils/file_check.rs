@@ -27,6 +27,17 @@ pub fn is_dir(name: &str) -> bool {
     Path::new(name).is_dir()
 }
 
+pub fn search_command(name: &str, path: &str) -> Option<String> {
+    for dir in path.split(":") {
+        let dir = if dir.is_empty() { "." } else { dir };
+        let cand = dir.to_owned() + "/" + name;
+        if is_regular_file(&cand) && is_executable(&cand) {
+            return Some(cand);
+        }
+    }
+    None
+}
+
 pub fn metadata_comp(left: &str, right: &str, tp: &str) -> bool {
     let (lmeta, rmeta) = match ( fs::metadata(left), fs::metadata(right) ) {
         ( Ok(lm), Ok(rm) ) => (lm, rm),
@@ -23,6 +23,10 @@ pub fn compare(word: &String, pattern: &str, extglob: bool) -> bool {
     candidates.iter().any(|c| c == "")
 }
 
+pub fn compare_nocase(word: &str, pattern: &str, extglob: bool) -> bool {
+    compare(&word.to_lowercase(), &pattern.to_lowercase(), extglob)
+}
+
 fn compare_internal(candidates: &mut Vec<String>, w: &Wildcard) {
     match w {
         Wildcard::Normal(s) => compare_normal(candidates, &s),
@@ -0,0 +1,549 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+enum BracketItem {
+    Char(char),
+    Range(char, char),
+    Class(String),
+}
+
+#[derive(Debug, Clone)]
+struct Bracket {
+    negated: bool,
+    items: Vec<BracketItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Assertion {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+    WordStart,
+    WordEnd,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Set(Bracket),
+    Assert(Assertion),
+    Group(usize, Box<Node>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat(Box<Node>, usize, Option<usize>),
+    BackRef(usize),
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Set(Bracket),
+    Assert(Assertion),
+    Split(usize, usize),
+    Jmp(usize),
+    Save(usize),
+    BackRef(usize),
+    Match,
+}
+
+#[derive(Debug)]
+pub struct Regex {
+    prog: Vec<Inst>,
+    groups: usize,
+    icase: bool,
+    has_backref: bool,
+}
+
+const DUP_MAX: usize = 255;
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn class_match(class: &str, c: char) -> bool {
+    match class {
+        "alpha"  => c.is_alphabetic(),
+        "digit"  => c.is_ascii_digit(),
+        "alnum"  => c.is_alphanumeric(),
+        "upper"  => c.is_uppercase(),
+        "lower"  => c.is_lowercase(),
+        "space"  => c.is_whitespace(),
+        "blank"  => c == ' ' || c == '\t',
+        "punct"  => c.is_ascii_punctuation(),
+        "print"  => ! c.is_control(),
+        "graph"  => ! c.is_control() && ! c.is_whitespace(),
+        "cntrl"  => c.is_control(),
+        "xdigit" => c.is_ascii_hexdigit(),
+        "word"   => is_word_char(c),
+        _ => false,
+    }
+}
+
+fn case_variants(c: char, icase: bool) -> Vec<char> {
+    match icase {
+        true  => {
+            let mut ans: Vec<char> = c.to_lowercase().chain(c.to_uppercase()).collect();
+            ans.push(c);
+            ans
+        },
+        false => vec![c],
+    }
+}
+
+fn char_eq(a: char, b: char, icase: bool) -> bool {
+    a == b || (icase && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+impl Bracket {
+    fn matches(&self, c: char, icase: bool) -> bool {
+        let found = case_variants(c, icase).iter().any(|v| self.contains(*v));
+        found != self.negated
+    }
+
+    fn contains(&self, c: char) -> bool {
+        self.items.iter().any(|item| match item {
+            BracketItem::Char(x) => *x == c,
+            BracketItem::Range(from, to) => *from <= c && c <= *to,
+            BracketItem::Class(name) => class_match(name, c),
+        })
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    groups: usize,
+    has_backref: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn alternation(&mut self, depth: usize) -> Result<Node, String> {
+        let mut branches = vec![self.concatenation(depth)?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            branches.push(self.concatenation(depth)?);
+        }
+
+        match branches.len() {
+            1 => Ok(branches.remove(0)),
+            _ => Ok(Node::Alt(branches)),
+        }
+    }
+
+    fn concatenation(&mut self, depth: usize) -> Result<Node, String> {
+        let mut nodes = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' || (c == ')' && depth > 0) {
+                break;
+            }
+
+            let atom = self.atom(depth, nodes.is_empty())?;
+            let node = self.repetition(atom)?;
+            nodes.push(node);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn atom(&mut self, depth: usize, head: bool) -> Result<Node, String> {
+        let c = self.chars[self.pos];
+        self.pos += 1;
+
+        match c {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assertion::Start)),
+            '$' => Ok(Node::Assert(Assertion::End)),
+            '[' => Ok(Node::Set(self.bracket()?)),
+            '\\' => self.escape(),
+            '(' => {
+                self.groups += 1;
+                let n = self.groups;
+                let inner = self.alternation(depth + 1)?;
+                if self.peek() != Some(')') {
+                    return Err("Unmatched ( or \\(".to_string());
+                }
+                self.pos += 1;
+                Ok(Node::Group(n, Box::new(inner)))
+            },
+            '*' | '+' | '?' if head => Ok(Node::Char(c)),
+            '*' | '+' | '?' => Err("Invalid preceding regular expression".to_string()),
+            _ => Ok(Node::Char(c)),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None    => return Err("Trailing backslash".to_string()),
+        };
+        self.pos += 1;
+
+        let class = |name: &str, negated: bool| Node::Set(Bracket {
+            negated,
+            items: vec![BracketItem::Class(name.to_string())],
+        });
+
+        match c {
+            '1'..='9' => {
+                let n = c.to_digit(10).unwrap() as usize;
+                if n > self.groups {
+                    return Err("Invalid back reference".to_string());
+                }
+                self.has_backref = true;
+                Ok(Node::BackRef(n))
+            },
+            'w' => Ok(class("word", false)),
+            'W' => Ok(class("word", true)),
+            's' => Ok(class("space", false)),
+            'S' => Ok(class("space", true)),
+            'b' => Ok(Node::Assert(Assertion::WordBoundary)),
+            'B' => Ok(Node::Assert(Assertion::NotWordBoundary)),
+            '<' => Ok(Node::Assert(Assertion::WordStart)),
+            '>' => Ok(Node::Assert(Assertion::WordEnd)),
+            '`' => Ok(Node::Assert(Assertion::Start)),
+            '\'' => Ok(Node::Assert(Assertion::End)),
+            _ => Ok(Node::Char(c)),
+        }
+    }
+
+    fn bracket(&mut self) -> Result<Bracket, String> {
+        let mut ans = Bracket { negated: false, items: vec![] };
+        if self.peek() == Some('^') {
+            ans.negated = true;
+            self.pos += 1;
+        }
+
+        let mut first = true;
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None    => return Err("Unmatched [, [^, [:, [., or [=".to_string()),
+            };
+            self.pos += 1;
+
+            if c == ']' && ! first {
+                return Ok(ans);
+            }
+            first = false;
+
+            let from = match (c, self.peek()) {
+                ('[', Some(':')) => {
+                    ans.items.push(BracketItem::Class(self.bracket_word(':')?));
+                    continue;
+                },
+                ('[', Some(d)) if d == '.' || d == '=' => {
+                    let s = self.bracket_word(d)?;
+                    s.chars().next().unwrap_or(' ')
+                },
+                _ => c,
+            };
+
+            if self.peek() == Some('-') && self.chars.get(self.pos+1).is_some_and(|c| *c != ']') {
+                self.pos += 1;
+                let to = self.chars[self.pos];
+                self.pos += 1;
+                if to < from {
+                    return Err("Invalid range end".to_string());
+                }
+                ans.items.push(BracketItem::Range(from, to));
+            }else{
+                ans.items.push(BracketItem::Char(from));
+            }
+        }
+    }
+
+    fn bracket_word(&mut self, delim: char) -> Result<String, String> {
+        self.pos += 1;
+        let mut ans = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == delim && self.peek() == Some(']') {
+                self.pos += 1;
+                if delim == ':'
+                && ! ["alpha", "digit", "alnum", "upper", "lower", "space", "blank",
+                      "punct", "print", "graph", "cntrl", "xdigit", "word"].contains(&ans.as_str()) {
+                    return Err("Invalid character class name".to_string());
+                }
+                return Ok(ans);
+            }
+            ans.push(c);
+        }
+        Err("Unmatched [, [^, [:, [., or [=".to_string())
+    }
+
+    fn repetition(&mut self, mut node: Node) -> Result<Node, String> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => self.interval()?,
+                _ => return Ok(node),
+            };
+            self.pos += 1;
+            node = Node::Repeat(Box::new(node), min, max);
+        }
+    }
+
+    /* parses {n}, {n,}, {,m} and {n,m} */
+    fn interval(&mut self) -> Result<(usize, Option<usize>), String> {
+        let rest: String = self.chars[self.pos+1..].iter().collect();
+        let end = match rest.find('}') {
+            Some(e) => e,
+            None    => return Err("Unmatched \\{".to_string()),
+        };
+
+        let err = || "Invalid content of \\{\\}".to_string();
+        let num = |s: &str| match s {
+            "" => Ok(None),
+            _  => s.parse::<usize>().map(Some).map_err(|_| err()),
+        };
+
+        let body = &rest[..end];
+        let (min, max) = match body.split_once(',') {
+            Some((a, b)) => (num(a)?.unwrap_or(0), num(b)?),
+            None         => match num(body)? {
+                Some(n) => (n, Some(n)),
+                None    => return Err(err()),
+            },
+        };
+
+        if min > DUP_MAX || max.is_some_and(|m| m < min || m > DUP_MAX) {
+            return Err(err());
+        }
+
+        self.pos += end + 1; //the position of '}'
+        Ok((min, max))
+    }
+}
+
+struct Compiler {
+    prog: Vec<Inst>,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> usize {
+        self.prog.push(inst);
+        self.prog.len() - 1
+    }
+
+    fn compile(&mut self, node: &Node) {
+        match node {
+            Node::Char(c) => { self.emit(Inst::Char(*c)); },
+            Node::Any => { self.emit(Inst::Any); },
+            Node::Set(b) => { self.emit(Inst::Set(b.clone())); },
+            Node::Assert(a) => { self.emit(Inst::Assert(a.clone())); },
+            Node::BackRef(n) => { self.emit(Inst::BackRef(*n)); },
+            Node::Group(n, inner) => {
+                self.emit(Inst::Save(n*2));
+                self.compile(inner);
+                self.emit(Inst::Save(n*2+1));
+            },
+            Node::Concat(nodes) => nodes.iter().for_each(|n| self.compile(n)),
+            Node::Alt(branches) => self.compile_alt(branches),
+            Node::Repeat(inner, min, max) => self.compile_repeat(inner, *min, *max),
+        }
+    }
+
+    fn compile_alt(&mut self, branches: &[Node]) {
+        let mut jumps = vec![];
+        for (i, b) in branches.iter().enumerate() {
+            if i == branches.len() - 1 {
+                self.compile(b);
+                break;
+            }
+
+            let split = self.emit(Inst::Split(0, 0));
+            self.compile(b);
+            jumps.push(self.emit(Inst::Jmp(0)));
+            self.prog[split] = Inst::Split(split + 1, self.prog.len());
+        }
+
+        let end = self.prog.len();
+        for j in jumps {
+            self.prog[j] = Inst::Jmp(end);
+        }
+    }
+
+    fn compile_repeat(&mut self, inner: &Node, min: usize, max: Option<usize>) {
+        for _ in 0..min {
+            self.compile(inner);
+        }
+
+        match max {
+            None => {
+                let split = self.emit(Inst::Split(0, 0));
+                self.compile(inner);
+                self.emit(Inst::Jmp(split));
+                self.prog[split] = Inst::Split(split + 1, self.prog.len());
+            },
+            Some(max) => {
+                let mut splits = vec![];
+                for _ in min..max {
+                    splits.push(self.emit(Inst::Split(0, 0)));
+                    self.compile(inner);
+                }
+                let end = self.prog.len();
+                for s in splits {
+                    self.prog[s] = Inst::Split(s + 1, end);
+                }
+            },
+        }
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str, icase: bool) -> Result<Regex, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            groups: 0,
+            has_backref: false,
+        };
+
+        let node = parser.alternation(0)?;
+
+        let mut compiler = Compiler { prog: vec![Inst::Save(0)] };
+        compiler.compile(&node);
+        compiler.emit(Inst::Save(1));
+        compiler.emit(Inst::Match);
+
+        Ok(Regex {
+            prog: compiler.prog,
+            groups: parser.groups,
+            icase,
+            has_backref: parser.has_backref,
+        })
+    }
+
+    fn assert(&self, a: &Assertion, chars: &[char], pos: usize) -> bool {
+        let before = pos > 0 && is_word_char(chars[pos-1]);
+        let after = pos < chars.len() && is_word_char(chars[pos]);
+        match a {
+            Assertion::Start => pos == 0,
+            Assertion::End => pos == chars.len(),
+            Assertion::WordBoundary => before != after,
+            Assertion::NotWordBoundary => before == after,
+            Assertion::WordStart => ! before && after,
+            Assertion::WordEnd => before && ! after,
+        }
+    }
+
+    /* explores all paths and keeps the longest match (POSIX leftmost-longest) */
+    fn run(&self, chars: &[char], start: usize) -> Option<Vec<Option<usize>>> {
+        let mut best: Option<Vec<Option<usize>>> = None;
+        let mut visited = HashSet::new();
+        let mut visited_with_caps = HashSet::new();
+        let mut stack = vec![(0, start, vec![None; (self.groups+1)*2])];
+
+        while let Some((mut pc, mut pos, mut caps)) = stack.pop() {
+            loop {
+                let first_visit = match self.has_backref {
+                    true  => visited_with_caps.insert((pc, pos, caps.clone())),
+                    false => visited.insert((pc, pos)),
+                };
+                if ! first_visit {
+                    break;
+                }
+
+                match &self.prog[pc] {
+                    Inst::Char(c) => {
+                        if pos >= chars.len() || ! char_eq(chars[pos], *c, self.icase) {
+                            break;
+                        }
+                        pos += 1;
+                        pc += 1;
+                    },
+                    Inst::Any => {
+                        if pos >= chars.len() {
+                            break;
+                        }
+                        pos += 1;
+                        pc += 1;
+                    },
+                    Inst::Set(b) => {
+                        if pos >= chars.len() || ! b.matches(chars[pos], self.icase) {
+                            break;
+                        }
+                        pos += 1;
+                        pc += 1;
+                    },
+                    Inst::Assert(a) => {
+                        if ! self.assert(a, chars, pos) {
+                            break;
+                        }
+                        pc += 1;
+                    },
+                    Inst::Split(x, y) => {
+                        stack.push((*y, pos, caps.clone()));
+                        pc = *x;
+                    },
+                    Inst::Jmp(x) => pc = *x,
+                    Inst::Save(n) => {
+                        caps[*n] = Some(pos);
+                        pc += 1;
+                    },
+                    Inst::BackRef(n) => {
+                        let (s, e) = match (caps[n*2], caps[n*2+1]) {
+                            (Some(s), Some(e)) => (s, e),
+                            _ => break,
+                        };
+                        let len = e - s;
+                        if pos + len > chars.len()
+                        || ! (0..len).all(|i| char_eq(chars[s+i], chars[pos+i], self.icase)) {
+                            break;
+                        }
+                        pos += len;
+                        pc += 1;
+                    },
+                    Inst::Match => {
+                        let longer = match &best {
+                            Some(b) => b[1] < Some(pos),
+                            None    => true,
+                        };
+                        if longer {
+                            best = Some(caps.clone());
+                        }
+                        break;
+                    },
+                }
+            }
+        }
+        best
+    }
+
+    /* returns the matched string and the substrings of the groups */
+    pub fn captures(&self, s: &str) -> Option<Vec<String>> {
+        let chars: Vec<char> = s.chars().collect();
+        for start in 0..=chars.len() {
+            if let Some(caps) = self.run(&chars, start) {
+                let ans = (0..=self.groups).map(|n| match (caps[n*2], caps[n*2+1]) {
+                    (Some(s), Some(e)) if s <= e => chars[s..e].iter().collect(),
+                    _ => String::new(),
+                }).collect();
+                return Some(ans);
+            }
+        }
+        None
+    }
+}
+
+pub fn escape(s: &str) -> String {
+    let mut ans = String::new();
+    for c in s.chars() {
+        if "\\.[]()*+?{}|^$".contains(c) {
+            ans.push('\\');
+        }
+        ans.push(c);
+    }
+    ans
+}
@@ -105,6 +105,41 @@ res=$($com <<< 'while true ; do while true ; do break 2 ; done ; echo NG ; done
 res=$($com <<< 'while true ; do while true ; do break 10 ; done ; echo NG ; done ; echo OK')
 [ "$res" == "OK" ] || err $LINENO
 
+# continue command
+
+res=$($com <<< 'for i in 1 2 3 ; do [ $i = 2 ] && continue ; echo $i ; done')
+[ "$res" == "1
+3" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 ; do for j in a b ; do [ $j = b ] && continue 2 ; echo $i$j ; done ; echo NG ; done')
+[ "$res" == "1a
+2a" ] || err $LINENO
+
+res=$($com <<< 'for ((i=0; i<4; i++)) ; do [ $i -eq 1 ] && continue ; echo $i ; done')
+[ "$res" == "0
+2
+3" ] || err $LINENO
+
+res=$($com <<< 'i=0 ; while [ $i -lt 3 ] ; do i=$((i+1)) ; [ $i -eq 2 ] && continue ; echo $i ; done')
+[ "$res" == "1
+3" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 ; do while true ; do continue 10 ; done ; echo NG ; done ; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'for i in 1 2 ; do continue 0 ; echo NG ; done ; echo $?')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'f () { continue ; } ; for i in 1 2 ; do f ; echo $i ; done')
+[ "$res" == "1
+2" ] || err $LINENO
+
+res=$($com <<< 'x=0 ; for i in 1 2 ; do continue ; (( x++ )) ; { x=5 ; } ; done ; echo $x')
+[ "$res" == "0" ] || err $LINENO
+
+res=$($com <<< 'x=0 ; for i in 1 2 ; do for j in a ; do continue 2 ; (( x++ )) ; done ; { x=5 ; } ; done ; echo $x')
+[ "$res" == "0" ] || err $LINENO
+
 # read
 
 res=$($com <<< 'seq 2 | while read a ; do echo $a ; done ; echo $a ; echo A')
@@ -121,11 +156,150 @@ res=$($com <<< 'echo あ い う | while read a b ; do echo $a ; echo $b ; done'
 [ "$res" == "あ
 い う" ] || err $LINENO
 
+res=$($com <<< 'IFS=, read a b c <<< "1,,3 4"; echo "$a:$b:$c"; IFS=, read a b <<< "1,2,3,"; echo $b')
+[ "$res" == "1::3 4
+2,3," ] || err $LINENO
+
+res=$($com <<< 'IFS=: read a b <<< " 1:2: "; echo "[$a][$b]"; read a <<< "  x  "; echo "[$a]"')
+[ "$res" == "[ 1][2: ]
+[x]" ] || err $LINENO
+
+res=$($com <<< 'printf "a\\ b c\\\\\\nd\n" | (read x y; echo "[$x][$y]"); printf "a\\ b\n" | (read -r x y; echo "[$x][$y]")')
+[ "$res" == "[a b][cd]
+[a\][b]" ] || err $LINENO
+
+res=$($com <<< 'echo " a b " | (read; echo "[$REPLY]"); printf abc | (read x; echo $? $x)')
+[ "$res" == "[ a b ]
+1 abc" ] || err $LINENO
+
+res=$($com <<< 'printf "a b\nc" | (read -N 3 x y; echo "[$x][$y]"); printf "a b\nc" | (read -n 3 x y; echo "[$x][$y]")')
+[ "$res" == "[a b][]
+[a][b]" ] || err $LINENO
+
+res=$($com <<< 'printf "a:b;c" | (read -d ";" x; echo $x); echo "x,,y" | (IFS=, read -a arr; echo ${#arr[@]} ${arr[2]})')
+[ "$res" == "a:b
+3 y" ] || err $LINENO
+
+res=$($com <<< '(sleep 1; echo a) | (read -t 0.1 x; echo $?); echo b | (read -u 0 x; echo $x)')
+[ "$res" == "142
+b" ] || err $LINENO
+
+res=$($com <<< 'printf "l1\nl2\n" | (read a; read b; echo $a $b); read -z; echo $?')
+[ "$res" == "l1 l2
+2" ] || err $LINENO
+
+res=$($com <<< 'read -t abc x < /dev/null; echo $?; read -n abc x < /dev/null; echo $?; read -u 9 x; echo $?')
+[ "$res" == "1
+1
+1" ] || err $LINENO
+
+# mapfile
+
+res=$($com <<< 'seq 3 | (mapfile; echo ${#MAPFILE[@]} ${MAPFILE[2]}); seq 5 | (mapfile -s 1 -n 2 -t x; echo "${x[@]}"; cat)')
+[ "$res" == "3 3
+2 3
+4
+5" ] || err $LINENO
+
+res=$($com <<< 'x=(p q r); mapfile -t -O 1 x <<< "z"; echo "${x[@]}"; printf "a,b" | (readarray -t -d , y; echo ${y[1]})')
+[ "$res" == "p z r
+b" ] || err $LINENO
+
+res=$($com <<< 'unset a; printf "p\nq\n" | (mapfile -t -O 5 a; declare -p a)')
+[ "$res" == 'declare -a a=([5]="p" [6]="q")' ] || err $LINENO
+
+res=$($com <<< 'seq 3 | (mapfile -t -C "echo cb" -c 2 x; echo ${#x[@]}); mapfile 1a; echo $?')
+[ "$res" == "cb 1 2
+3
+1" ] || err $LINENO
+
+# exec command
+
+res=$($com <<< 'exec 3> /tmp/$$-exec; echo abc >&3; exec 3>&-; cat /tmp/$$-exec; rm /tmp/$$-exec')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'exec 4<<< def; read l <&4; echo $l')
+[ "$res" == "def" ] || err $LINENO
+
+res=$($com <<< '( exec > /dev/null 2>&1; echo out; echo err >&2 ); echo ok')
+[ "$res" == "ok" ] || err $LINENO
+
+res=$($com <<< 'exec echo a; echo b')
+[ "$res" == "a" ] || err $LINENO
+
+res=$($com <<< '( exec -a xyz bash -c "echo \$0" ); ( exec -l bash -c "echo \$0" )')
+[ "$res" == "xyz
+-bash" ] || err $LINENO
+
+res=$($com <<< 'export A=1; ( exec -c printenv A ); echo $?')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'exec no_such_command; echo never')
+[ "$?" == "127" ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
 # set command
 
 res=$($com <<< 'set -- a b c ; echo $2')
 [ "$res" == "b" ] || err $LINENO
 
+res=$($com <<< 'set -o nounset -C; echo $- $SHELLOPTS; set +uC; echo $-')
+[ "$res" == "huC hashall:noclobber:nounset
+h" ] || err $LINENO
+
+res=$($com <<< 'set -u; echo ${x-def} $#; echo $x; echo NG')
+[ "$res" == "def 0" ] || err $LINENO
+
+res=$($com <<< 'set -u; a=(1); echo ${a[0]} ${#a[@]} ${b[@]}X
+echo ${a[5]}; echo NG
+echo ${#b[@]}; echo NG
+(( z + 1 )); echo NG' 2>&1)
+[ "$res" == "1 1 X
+sush: a[5]: unbound variable
+sush: b: unbound variable
+sush:  z + 1 : z: unbound variable" ] || err $LINENO
+
+res=$($com <<< 'set -C; echo a > /tmp/$$-nc; echo b > /tmp/$$-nc; echo c >| /tmp/$$-nc; cat /tmp/$$-nc; rm /tmp/$$-nc')
+[ "$res" == "c" ] || err $LINENO
+
+res=$($com <<< 'set -f; echo /et*; set +o noglob; echo /et*')
+[ "$res" == "/et*
+/etc" ] || err $LINENO
+
+res=$($com <<< 'set -a; x=1; bash -c "echo \$x"; set -n
+echo NG')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'set -n; echo NG; echo NG &')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com -c 'echo OK; set -n && echo NG')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -o pipefail; [[ -o pipefail && -o hashall ]] && set +o | grep -c -- -o; set -o foo')
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "2" ] || err $LINENO
+
+res=$($com <<< 'set -bm; sleep 0.1 & sleep 0.3; echo x; set +m; fg' 2>&1 | tail -n 3)
+[ "$res" == "[1]+  Done     sleep 0.1
+x
+sush: fg: no job control" ] || err $LINENO
+
+# hash command
+
+res=$($com <<< 'hash; ls > /dev/null; ls > /dev/null; hash; hash -t ls; hash -d ls; hash -t ls')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "hash: hash table empty
+hits	command
+   2	$(command -v ls)
+$(command -v ls)" ] || err $LINENO
+
+res=$($com <<< 'hash -p /bin/echo foo; foo ok; hash -l; PATH=$PATH; hash; set +h; hash')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "ok
+builtin hash -p /bin/echo foo
+hash: hash table empty" ] || err $LINENO
+
 # shopt command
 
 res=$($com <<< 'shopt -u extglob ; echo @(a)')
@@ -136,5 +310,265 @@ echo @(a)')
 [ "$?" == "2" ] || err $LINENO
 [ "$res" == "" ] || err $LINENO
 
+res=$($com <<< 'echo a | read x; echo "[$x]"; shopt -s lastpipe; echo b | read x; echo $x; seq 3 | while read n; do s=$n; done; echo $s')
+[ "$res" == "[]
+b
+3" ] || err $LINENO
+
+res=$($com <<< 'alias e=echo
+e NG
+shopt -s expand_aliases
+alias e=echo
+e OK; shopt lastpipe autocd')
+[ "$res" == "OK
+lastpipe        off
+autocd          off" ] || err $LINENO
+
+# declare command
+
+res=$($com <<< 'declare -A m=([a]=1 [b]="x y"); m[c]=3; k=b; echo ${m[a]} ${m[$k]} ${#m[@]} ${!m[@]}')
+[ "$res" == "1 x y 3 a b c" ] || err $LINENO
+
+res=$($com <<< 'declare -A m=([a]=1 [b]="x y"); declare -p m')
+[ "$res" == 'declare -A m=([a]="1" [b]="x y" )' ] || err $LINENO
+
+res=$($com <<< 'declare -A m=([a]=1 [b]="x y"); eval "$(declare -p m)"; echo ${m[b]}')
+[ "$res" == "x y" ] || err $LINENO
+
+res=$($com <<< 'declare -a a=(x y); a[1]=Y; declare -p a; s=\"\$; declare -p s')
+[ "$res" == 'declare -a a=([0]="x" [1]="Y")
+declare -- s="\"\$"' ] || err $LINENO
+
+res=$($com <<< 'declare -i n=3+4; n=n*2; typeset -u u=abc; declare -l l=ABC; echo $n $u $l')
+[ "$res" == "14 ABC abc" ] || err $LINENO
+
+res=$($com <<< 'declare -r r=1; declare r=2; echo $? $r; declare -p r')
+[ "$res" == '1 1
+declare -r r="1"' ] || err $LINENO
+
+res=$($com <<< 'x=5; declare -n ref=x; ref=7; echo $ref $x')
+[ "$res" == "7 7" ] || err $LINENO
+
+res=$($com <<< 'f () { declare v=1; declare -g g=2; }; f; echo "$v" $g')
+[ "$res" == " 2" ] || err $LINENO
+
+res=$($com <<< 'declare -p nosuch')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'declare foo; declare -p foo; echo ${foo-unset}')
+[ "$res" == 'declare -- foo
+unset' ] || err $LINENO
+
+res=$($com <<< 'declare -A m=([x]=1 [y]=2); unset "m[x]"; echo ${#m[@]} ${!m[@]}')
+[ "$res" == "1 y" ] || err $LINENO
+
+res=$($com <<< 'a=(1 2 3); i=1; unset "a[i]" b; echo ${a[@]}')
+[ "$res" == "1 3" ] || err $LINENO
+
+res=$($com <<< 'a=(x y z); a[10]=w; unset "a[1]"; echo ${#a[@]} ${!a[@]} ${a[2]}; declare -p a')
+[ "$res" == '3 0 2 10 z
+declare -a a=([0]="x" [2]="z" [10]="w")' ] || err $LINENO
+
+res=$($com <<< 'a=([3]=x y); eval "$(declare -p a)"; echo ${!a[@]} ${a[4]}')
+[ "$res" == "3 4 y" ] || err $LINENO
+
+res=$($com <<< 'declare -A m=(["a b"]=1 [c]=2); for k in "${!m[@]}"; do echo "[$k]"; done')
+[ "$res" == "[a b]
+[c]" ] || err $LINENO
+
+res=$($com <<< 'declare -i a=(1+1 2*3); echo ${a[@]}')
+[ "$res" == "2 6" ] || err $LINENO
+
+res=$($com <<< 'f () { local -A h; h[a]=1; local -a y=(2 3); local x; declare -p x; echo ${h[a]} ${y[1]} [$x]; x=4; }; x=5; f; echo ${h[a]} $x')
+[ "$res" == 'declare -- x
+1 3 []
+5' ] || err $LINENO
+
+res=$($com <<< 'f () { local a=1 1bad b=2; echo $? $a $b; }; f' 2> /dev/null)
+[ "$res" == "1 1 2" ] || err $LINENO
+
+# export and readonly
+
+res=$($com <<< 'A=1; export A; B=2; export C=3; bash -c "echo \$A \$B \$C"')
+[ "$res" == "1 3" ] || err $LINENO
+
+res=$($com <<< 'export A=1; export -n A; bash -c "echo [\$A]"; A=5 bash -c "echo \$A"')
+[ "$res" == "[]
+5" ] || err $LINENO
+
+res=$($com <<< 'export A=1; export -p | grep " A="')
+[ "$res" == 'declare -x A="1"' ] || err $LINENO
+
+res=$($com <<< 'readonly R=1; R=2; echo NG')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'readonly R=1; R=2 true; echo $? $R; (R=3; echo NG); echo $?')
+[ "$res" == "1 1
+1" ] || err $LINENO
+
+res=$($com <<< 'readonly R=1; (( R=3 )); echo $R; unset R; echo $? $R')
+[ "$res" == "1
+1 1" ] || err $LINENO
+
+res=$($com <<< 'readonly R=1; (( R=3 ))' 2>&1)
+[ "$res" == "sush: R: readonly variable" ] || err $LINENO
+
+res=$($com <<< 'readonly R=1; for R in a; do echo NG; done; echo $? $R')
+[ "$res" == "1 1" ] || err $LINENO
+
+res=$($com <<< 'readonly R; f () { local R=3; }; f')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'readonly R=1; readonly -p | grep " R="')
+[ "$res" == 'declare -r R="1"' ] || err $LINENO
+
+# trap command
+
+res=$($com <<< 'trap "echo bye" EXIT; echo a')
+[ "$res" == "a
+bye" ] || err $LINENO
+
+res=$($com <<< 'trap "echo err \$?" ERR; false; true && false || true; if false; then :; else :; fi')
+[ "$res" == "err 1" ] || err $LINENO
+
+res=$($com <<< 'trap "echo got" USR1; kill -USR1 $$; echo after')
+[ "$res" == "got
+after" ] || err $LINENO
+
+res=$($com <<< 'trap "echo \$BASH_COMMAND" DEBUG; echo x; trap - DEBUG; echo y')
+[ "$res" == "echo x
+x
+trap - DEBUG
+y" ] || err $LINENO
+
+res=$($com <<< 'f () { trap "echo ret" RETURN; echo in; }; f; trap - RETURN')
+[ "$res" == "in
+ret" ] || err $LINENO
+
+res=$($com <<< 'trap "echo it'"'"'s" INT 15; trap -p')
+[ "$res" == "trap -- 'echo it'\\''s' SIGINT
+trap -- 'echo it'\\''s' SIGTERM" ] || err $LINENO
+
+res=$($com <<< 'trap "echo x" EXIT; (echo sub)')
+[ "$res" == "sub
+x" ] || err $LINENO
+
+res=$($com <<< 'trap "" foo')
+[ "$?" == "1" ] || err $LINENO
+
+# printf command
+
+res=$($com <<< 'printf "%s-%d|%5s|%-3s|%05.1f\n" a 12 b c 3.14159')
+[ "$res" == "a-12|    b|c  |003.1" ] || err $LINENO
+
+res=$($com <<< 'printf "%x %X %o %#x %u %c %%\n" 255 255 8 255 -1 hello')
+[ "$res" == "ff FF 10 0xff 18446744073709551615 h %" ] || err $LINENO
+
+res=$($com <<< 'printf "%e %g %g %G\n" 1234.5 0.0001 123456789 1e20')
+[ "$res" == "1.234500e+03 0.0001 1.23457e+08 1E+20" ] || err $LINENO
+
+res=$($com <<< 'printf "%*d|%-*d|%.*f|\n" 5 1 4 2 2 3.14159')
+[ "$res" == "    1|2   |3.14|" ] || err $LINENO
+
+res=$($com <<< 'printf "%s=%s\n" a 1 b 2 c')
+[ "$res" == "a=1
+b=2
+c=" ] || err $LINENO
+
+res=$($com <<< 'printf "%b|%q\n" "a\tb" "x y"')
+[ "$res" == "a	b|x\ y" ] || err $LINENO
+
+res=$($com <<< 'printf "%b|\n" "x\cy" z; printf "\x41\101\n"')
+[ "$res" == "xAA" ] || err $LINENO
+
+res=$($com <<< 'printf -v v "%03d" 7; a=(x y); printf -v "a[1]" "%s!" z; echo $v ${a[@]}')
+[ "$res" == "007 x z!" ] || err $LINENO
+
+res=$($com <<< 'declare -A h; printf -v "h[k]" "%s" hi; echo ${h[k]}')
+[ "$res" == "hi" ] || err $LINENO
+
+res=$($com <<< 'TZ=JST-9 printf "%(%Y-%m-%d %H)T\n" 86400')
+[ "$res" == "1970-01-02 09" ] || err $LINENO
+
+res=$($com <<< 'TZ=JST-9 printf "%()T\n" 3600')
+[ "$res" == "10:00:00" ] || err $LINENO
+
+res=$($com <<< 'printf "%d\n" abc')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'printf')
+[ "$?" == "2" ] || err $LINENO
+
+# echo command
+
+res=$($com <<< 'echo -n a; echo -e "b\tc\x41\0101"; echo -E "d\te"; echo -neX f')
+[ "$res" == "ab	cAA
+d\te
+-neX f" ] || err $LINENO
+
+res=$($com <<< 'echo -e "a\cb"; echo c')
+[ "$res" == "ac" ] || err $LINENO
+
+res=$($com <<< 'shopt -s xpg_echo; echo "a\tb"; echo -E "a\tb"')
+[ "$res" == "a	b
+a\tb" ] || err $LINENO
+
+res=$($com <<< 'echo x >&-; echo $?' 2>&1)
+[ "$res" == "sush: echo: write error: Bad file descriptor
+1" ] || err $LINENO
+
+res=$($com <<< 'PATH=/nowhere; echo ok')
+[ "$res" == "ok" ] || err $LINENO
+
+# test command
+
+res=$($com <<< 'test; echo $?; test ""; echo $?; test a; echo $?; [ -n ]; echo $?')
+[ "$res" == "1
+1
+0
+0" ] || err $LINENO
+
+res=$($com <<< '[ ! a ]; echo $?; [ -z "" ]; echo $?; [ a != a ]; echo $?; [ 1 -lt 2 ]; echo $?')
+[ "$res" == "1
+0
+1
+0" ] || err $LINENO
+
+res=$($com <<< '[ a -a "" ]; echo $?; [ a -o "" ]; echo $?; [ "(" a ")" ]; echo $?; [ ! a = b ]; echo $?')
+[ "$res" == "1
+0
+0
+0" ] || err $LINENO
+
+res=$($com <<< 'test a -o "" -a ""; echo $?; test "(" a -o "" ")" -a ""; echo $?; test ! "" -a a; echo $?')
+[ "$res" == "0
+1
+0" ] || err $LINENO
+
+res=$($com <<< '[ -f /etc/passwd -a -d /etc ]; echo $?; [ -d /etc/passwd ]; echo $?')
+[ "$res" == "0
+1" ] || err $LINENO
+
+res=$($com <<< '[ a -eq 1 ]')
+[ "$?" == "2" ] || err $LINENO
+
+res=$($com <<< '[ 010 -eq 8 ]; echo $?; [ 010 -eq 10 ]; echo $?; test " -08" -lt -7 -a 09 -gt 8; echo $?')
+[ "$res" == "1
+0
+0" ] || err $LINENO
+
+res=$($com <<< '[ 0x10 -eq 16 ]')
+[ "$?" == "2" ] || err $LINENO
+
+res=$($com <<< '[ 1.5 -eq 1 ]')
+[ "$?" == "2" ] || err $LINENO
+
+res=$($com <<< '[ a b ]')
+[ "$?" == "2" ] || err $LINENO
+
+res=$($com <<< '[ a = a')
+[ "$?" == "2" ] || err $LINENO
+
 echo $0 >> ./ok
 
@@ -165,6 +165,57 @@ res=$($com <<< 'touch /tmp/rusty_bash_x ; while [ -f /tmp/rusty_bash_x ] ; do ec
 [ "$res" == "wait
 wait" ] || err $LINENO
 
+### UNTIL TEST ###
+
+res=$($com <<< 'i=0; until [ $i -ge 2 ] ; do echo $i ; i=$((i+1)) ; done; until true ; do echo NG ; done; echo $?')
+[ "$res" == "0
+1
+0" ] || err $LINENO
+
+res=$($com <<< 'f () until [ -n "$x" ] ; do x=set ; echo in f ; done ; f')
+[ "$res" == "in f" ] || err $LINENO
+
+### SELECT TEST ###
+
+res=$($com <<< 'PS3=">"; select v in a b c; do echo "$v $REPLY"; [ "$REPLY" = 1 ] && break; done 2> /dev/null <<< "2
+x
+1"')
+[ "$res" == "b 2
+ x
+a 1" ] || err $LINENO
+
+res=$($com <<< 'set p q; select v ; do echo $v; break; done 2>&1 <<< 2')
+[ "$res" == "1) p
+2) q
+#? q" ] || err $LINENO
+
+res=$($com <<< 'f () select v in x y ; do echo $v ; break ; done ; f <<< 2 2> /dev/null; select v in a; do :; done < /dev/null; echo $?')
+[ "$res" == "y
+
+1" ] || err $LINENO
+
+### COPROC TEST ###
+
+res=$($com <<< 'coproc cat; echo abc >&${COPROC[1]}; read -u ${COPROC[0]} l; echo $l')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'coproc UP { while read l; do echo ${l^^}; done; }; echo abc >&${UP[1]}; read -u ${UP[0]} l; echo $l')
+[ "$res" == "ABC" ] || err $LINENO
+
+res=$($com <<< 'coproc NAME { read a; echo $a$a; cat; }; echo x >&${NAME[1]}; read -u ${NAME[0]} l; echo $l ${#NAME[@]}')
+[ "$res" == "xx 2" ] || err $LINENO
+
+res=$($com <<< 'coproc sleep 1; ps -p $COPROC_PID -o comm=')
+[ "$res" == "sleep" ] || err $LINENO
+
+res=$($com -c 'coproc { cat; echo end; }; echo abc >&${COPROC[1]}; exec {COPROC[1]}>&-; cat <&${COPROC[0]}; wait; echo ${COPROC_PID-unset} ${COPROC[0]-unset}')
+[ "$res" == "abc
+end
+unset unset" ] || err $LINENO
+
+res=$($com -c 'coproc cat; exec {COPROC[1]}>&-; wait $COPROC_PID; echo ${COPROC_PID-unset}')
+[ "$res" == "unset" ] || err $LINENO
+
 ### FOR TEST ###
 
 res=$($com <<< 'set a b c ; for x ; do echo $x ; done')
@@ -215,7 +266,7 @@ res=$($com <<< 'if [ "a" == "a" ] ; then echo aa; fi')
 res=$($com <<< 'if [ "a" == "b" ] ; then echo aa; else echo bb; fi')
 [ "$res" = "bb" ] || err $LINENO
 
-res=$($com <<< 'if [ "a" == "b" ] ; then echo aa; fi' || echo x)
+res=$($com <<< 'if [ "a" == "b" ] ; then echo aa; fi' && echo x)
 [ "$res" = "x" ] || err $LINENO
 
 res=$($com <<< 'if [ "a" == "b" ] ; then echo a ; fi ; if [ "b" == "b" ] ; then echo bb ; fi')
@@ -810,6 +861,9 @@ res=$($com -c '[[ -v LANGLANG ]]')
 res=$($com -c 'A= ; [[ -v A ]]')
 [ "$?" = "0" ] || err $LINENO
 
+res=$($com -c 'declare A; [[ -v A ]]')
+[ "$?" = "1" ] || err $LINENO
+
 res=$($com -c 'B=A; A= ; [[ -v $B ]]')
 [ "$?" = "0" ] || err $LINENO
 
@@ -882,4 +936,56 @@ res=$($com -c '[[ -a /etc/passwd && -a /etc/passwdaaa ]]')
 res=$($com -c '[[ -a /etc/passwdaaaa || -a /etc/passwd ]]')
 [ "$?" = "0" ] || err $LINENO
 
+# regex
+
+res=$($com <<< 'x=v12.34 ; [[ $x =~ ^v([0-9]+)\.([0-9]+)$ ]] ; echo $? ${BASH_REMATCH[@]}')
+[ "$res" = "0 v12.34 12 34" ] || err $LINENO
+
+res=$($com <<< '[[ abc =~ "a.c" ]] ; echo $? ; [[ a.c =~ "a.c" ]] ; echo $? ; [[ abc =~ a\.c ]] ; echo $?')
+[ "$res" = "1
+0
+1" ] || err $LINENO
+
+res=$($com <<< 're="^(a|ab)(c|bcd)(d*)$" ; [[ abcd =~ $re ]] ; echo ${BASH_REMATCH[0]} ${BASH_REMATCH[1]} ${BASH_REMATCH[2]}')
+[ "$res" = "abcd a bcd" ] || err $LINENO
+
+res=$($com <<< '[[ x1y =~ [[:digit:]]+ ]] && echo ${BASH_REMATCH[0]} ; [[ "foo bar" =~ (o b) ]] && echo ${BASH_REMATCH[1]}')
+[ "$res" = "1
+o b" ] || err $LINENO
+
+res=$($com <<< 're="(abc)\1" ; [[ xabcabc =~ $re ]] && echo ${BASH_REMATCH[0]} ; [[ aaa =~ a{2} ]] && echo ${BASH_REMATCH[0]}')
+[ "$res" = "abcabc
+aa" ] || err $LINENO
+
+res=$($com <<< '[[ ABC =~ a[b]c ]] ; echo $? ; shopt -s nocasematch ; [[ ABC =~ a[b]c ]] ; echo $?')
+[ "$res" = "1
+0" ] || err $LINENO
+
+res=$($com <<< '[[ abc =~ x ]] ; echo $? ${#BASH_REMATCH[@]}')
+[ "$res" = "1 0" ] || err $LINENO
+
+res=$($com <<< 're="a(" ; [[ a =~ $re ]] ; echo $?')
+[ "$res" = "2" ] || err $LINENO
+
+res=$($com <<< 're="a(" ; [[ a =~ $re ]]' 2>&1)
+[[ "$res" =~ "a(: Unmatched ( or" ]] || err $LINENO
+
+# pattern matching
+
+res=$($com <<< '[[ abc == a* ]] && [[ abc = a?c ]] && [[ abc != b* ]] && echo OK')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< 'p="a*"; [[ abc == "a*" ]] || [[ abc == "$p" ]] || [[ abc != $p ]] || echo OK')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< '[[ abc == @(x|abc) ]] && [[ abd == !(abc) ]] && echo OK')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< '[[ ABC == a* ]] || echo NG; shopt -s nocasematch; [[ ABC == a* ]] && echo OK')
+[ "$res" = "NG
+OK" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nocasematch; case XyZ in xyz) echo OK ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
 echo $0 >> ./ok
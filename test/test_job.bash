@@ -23,4 +23,53 @@ echo "$res" | grep -F '[3]+ ' || err $LINENO
 res=$($com <<< 'sleep 5 | rev | cat & sleep 1 ; killall -SIGSTOP cat ; jobs')
 echo "$res" | grep Stopped || err $LINENO
 
+res=$($com <<< 'true; sleep 10 & kill %sleep; wait %1; echo $?')
+[ "$res" == "143" ] || err $LINENO
+
+res=$($com <<< 'sleep 10 & sleep 10 & kill %sl; echo $?; kill %?ee; echo $?; kill %1 %2' 2>&1 | grep -c ambiguous)
+[ "$res" == "2" ] || err $LINENO
+
+res=$($com <<< 'sleep 10 & sleep 10 & kill %sl; echo $?; kill %1 %2' 2> /dev/null)
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'suspend; echo $?' 2>&1)
+[ "$res" == "sush: suspend: cannot suspend: no job control
+1" ] || err $LINENO
+
+res=$($com <<< 'sleep 10 & sleep 20 & kill -s KILL %?20; wait %2; echo $?')
+[ "$res" == "137" ] || err $LINENO
+
+res=$($com <<< 'sleep 10 & kill -s HUP -- %1; wait %1; echo $?; sleep 10 & kill -- %1; wait %1; echo $?; kill -0 -- $$; echo $?')
+[ "$res" == "129
+143
+0" ] || err $LINENO
+
+res=$($com <<< 'kill -l 15; kill -l 143; kill -l TERM')
+[ "$res" == "TERM
+TERM
+15" ] || err $LINENO
+
+res=$($com <<< 'kill -n 0 $$; echo $?; kill %5; echo $?')
+[ "$res" == "0
+1" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & disown; jobs')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'sleep 5 & disown -h %1; jobs')
+echo "$res" | grep -F '[1]+ ' || err $LINENO
+
+res=$($com <<< 'sleep 5 & sleep 1 & jobs -p; wait -n -p v; echo $?; echo $v')
+[ "$(echo "$res" | tail -n 2)" == "0
+$(echo "$res" | sed -n 2p)" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 2 & jobs -p')
+[ "$(echo "$res" | wc -l)" == "2" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & jobs -l')
+echo "$res" | grep -E '^\[1\]\+  [0-9]+ Running' || err $LINENO
+
+res=$($com <<< 'sleep 1 & jobs -s')
+[ "$res" == "" ] || err $LINENO
+
 echo $0 >> ./ok
@@ -286,12 +286,132 @@ res=$($com <<< 'pwd 200>&100')
 res=$($com <<< 'ls 200>&100')
 [ "$?" == "1" ] || err $LINENO
 
+res=$($com <<< 'echo abc > /tmp/$$-rw; cat <> /tmp/$$-rw; rm /tmp/$$-rw')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'echo x >| /tmp/$$-cl; cat /tmp/$$-cl; rm /tmp/$$-cl')
+[ "$res" == "x" ] || err $LINENO
+
+res=$($com <<< 'echo in > /tmp/$$-in; cat 3< /tmp/$$-in <&3; read a 4< /tmp/$$-in <&4; echo $a; rm /tmp/$$-in')
+[ "$res" == "in
+in" ] || err $LINENO
+
+res=$($com <<< 'echo in > /tmp/$$-mv; cat 3< /tmp/$$-mv 4<&3- /dev/fd/4; cat <&3; echo $?; rm /tmp/$$-mv')
+[ "$res" == "in
+1" ] || err $LINENO
+
+res=$($com <<< '{ echo out; echo err >&2; } 3>&1 1>&2 2>&3 3>&-' 2> /dev/null)
+[ "$res" == "err" ] || err $LINENO
+
+res=$($com <<< 'echo a >&-; echo b; echo c 5<&-; echo d 5<<< abc')
+[ "$res" == "b
+c
+d" ] || err $LINENO
+
+res=$($com <<< 'true 15<<< abc 16>&15-; true 17> /dev/null; [ -e /proc/$$/fd/15 ] || [ -e /proc/$$/fd/16 ] || [ -e /proc/$$/fd/17 ]; echo $?')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< '{ echo a; echo b >&2; } &> /tmp/$$-ap; { echo c >&2; } &>> /tmp/$$-ap; cat /tmp/$$-ap; rm /tmp/$$-ap')
+[ "$res" == "a
+b
+c" ] || err $LINENO
+
+res=$($com <<< 'true {fd}> /tmp/$$-fd; echo $(( fd >= 10 )); echo abc >&$fd; {fd}>&-; cat /tmp/$$-fd; rm /tmp/$$-fd')
+[ "$res" == "1
+abc" ] || err $LINENO
+
+res=$($com <<< 'true {a}<<< xyz {b}>&2; read l <&$a; echo $l $(( b > a )); {a}<&-; {b}>&-; [ -e /proc/$$/fd/$a ]; echo $?')
+[ "$res" == "xyz 1
+1" ] || err $LINENO
+
+res=$($com <<< '{x}>&-')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'exec {fd}> /nonexistent/dir/x; echo "[$fd]"' 2> /dev/null)
+[ "$res" == "[]" ] || err $LINENO
+
+res=$($com <<< 'echo a > /dev/stdout; echo b > /dev/fd/1; echo c 2> /dev/null > /dev/stderr')
+[ "$res" == "a
+b" ] || err $LINENO
+
+res=$($com <<< 'echo x > /dev/tcp/127.0.0.1/1; echo $?' 2> /dev/null)
+[ "$res" == "1" ] || err $LINENO
+
+if type python3 &> /dev/null ; then
+	python3 -c '
+import socket
+s = socket.socket()
+s.bind(("127.0.0.1", 0))
+s.listen(1)
+print(s.getsockname()[1], flush=True)
+c, _ = s.accept()
+c.sendall(b"got " + c.recv(100))
+c.close()' > /tmp/$$-port &
+	sleep 0.5
+	port=$(cat /tmp/$$-port)
+	res=$($com <<< "exec 3<>/dev/tcp/127.0.0.1/$port; echo abc >&3; read l <&3; echo \$l; exec 3>&-")
+	[ "$res" == "got abc" ] || err $LINENO
+	rm /tmp/$$-port
+fi
+
 # with expansion
 
 res=$($com <<< 'echo a > {a,b}' 2>&1)
 [ "$?" == "1" ] || err $LINENO
 [ "$res" == "sush: {a,b}: ambiguous redirect" ] || err $LINENO
 
+# here documents and here strings
+
+res=$($com <<< 'a=hoge
+cat <<EOF
+$a "b" $((1+2)) \$a $(echo c)
+EOF')
+[ "$res" == 'hoge "b" 3 $a c' ] || err $LINENO
+
+res=$($com <<< 'a=hoge
+cat <<"EOF" | rev
+$a
+EOF')
+[ "$res" == 'a$' ] || err $LINENO
+
+res=$($com <<< '	cat <<-EOF
+		abc
+	EOF')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'cat <<A <<B
+a
+A
+b
+B
+echo c')
+[ "$res" == "b
+c" ] || err $LINENO
+
+res=$($com <<< 'f () { cat <<EOF
+$1
+EOF
+}
+f hoge')
+[ "$res" == "hoge" ] || err $LINENO
+
+res=$($com <<< 'a="x  y"; cat <<< $a; cat <<< "$a" | wc -l')
+[ "$res" == "x  y
+1" ] || err $LINENO
+
+res=$($com -c 'cat <<EOF
+a \
+b
+EOF
+echo after' 2>&1)
+[ "$res" == "a b
+after" ] || err $LINENO
+
+res=$($com <<< 'cat <<EOF
+x\`y \$z \\w \a
+EOF')
+[ "$res" == 'x`y $z \w \a' ] || err $LINENO
+
 ### JOB PARSE TEST ###
 
 res=$($com <<< '&& echo a')
@@ -1134,6 +1254,60 @@ res=$($com <<< 'echo ${_A32*523j2}')
 res=$($com <<< 'set a b c;echo $@')
 [ "$res" == "a b c" ] || err $LINENO
 
+res=$($com <<< 'set a b c;echo $# ${#}')
+[ "$res" == "3 3" ] || err $LINENO
+
+res=$($com <<< 'A=abcabc; B=(aa bbb c); echo ${#A} ${#B[@]} ${#B[1]}')
+[ "$res" == "6 3 3" ] || err $LINENO
+
+res=$($com <<< 'A=abcabc; echo ${A#*b} ${A##*b} ${A%b*} ${A%%b*}')
+[ "$res" == "cabc c abca a" ] || err $LINENO
+
+res=$($com <<< 'A=abcabc; echo ${A/b/X} ${A//b/X} ${A/#a/X} ${A/%c/X} ${A/b}')
+[ "$res" == "aXcabc aXcaXc Xbcabc abcabX acabc" ] || err $LINENO
+
+res=$($com <<< 'A=abcabc; echo ${A/"b"*/X} ${A/@(b|c)/X}')
+[ "$res" == "aX aXcabc" ] || err $LINENO
+
+res=$($com <<< 'A=abcabc; echo ${A:1} ${A:1:2} ${A: -2} ${A:1:-1} ${A:2*2}')
+[ "$res" == "bcabc bc bc bcab bc" ] || err $LINENO
+
+res=$($com <<< 'A=abc; echo ${A:1:-3}')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'A=abcabc; B=ABC; echo ${A^} ${A^^} ${A^^[ab]} ${B,} ${B,,}')
+[ "$res" == "Abcabc ABCABC ABcABc aBC abc" ] || err $LINENO
+
+res=$($com <<< 'B=(aa bb cc); echo ${B[@]/a/X} ${B[*]^^} ${B[@]:1} ${B[@]#?}')
+[ "$res" == "Xa bb cc AA BB CC bb cc a b c" ] || err $LINENO
+
+res=$($com <<< 'set a b c d; echo ${@:2} ${@:1:2} ${@: -1} ${@%[bc]}')
+[ "$res" == "b c d a b d a d" ] || err $LINENO
+
+res=$($com <<< 'A=; echo ${A-x}${U-y} ${A=x}${U=z}$U ${A+p}${U2+q}')
+[ "$res" == "y zz p" ] || err $LINENO
+
+res=$($com <<< 'A=a; echo ${A:-b} ${A-b}')
+[ "$res" == "a a" ] || err $LINENO
+
+res=$($com <<< 'A=abXc; E=; echo ${A//X/} ${A/X/} ${A/a/$E}; B=${A/X/}; echo $B')
+[ "$res" == "abc abc bXc
+abc" ] || err $LINENO
+
+res=$($com <<< 'A=5; echo "[${A:+}]" "[${A+}]"')
+[ "$res" == "[] []" ] || err $LINENO
+
+res=$($com <<< 'A=("a b" qc); for e in "${A[@]/q/Q}" "${A[@]:1}"; do echo "[$e]"; done')
+[ "$res" == "[a b]
+[Qc]
+[qc]" ] || err $LINENO
+
+res=$($com <<< 'A=(a b); set -- c d; for e in "x${A[@]}y" "${@}"; do echo "[$e]"; done')
+[ "$res" == "[xa]
+[by]
+[c]
+[d]" ] || err $LINENO
+
 # tilde
 
 res=$($com <<< 'echo ~ | grep -q /')
@@ -1187,6 +1361,32 @@ res=$($com <<< 'echo /"*"' | grep -F '*')
 res=$($com <<< 'echo @(あ|{い,う,})')
 [ "$res" == "@(あ|い) @(あ|う) @(あ|)" ] || err $LINENO
 
+res=$($com <<< 'mkdir -p /tmp/$$-glob/a/b; cd /tmp/$$-glob; touch x.rs a/y.rs a/b/z.rs .h.rs X.TXT
+echo *.rs; shopt -s dotglob; echo *.rs; shopt -s globstar; echo **/*.rs; echo a/**; echo */
+shopt -s nocaseglob; echo *.txt; shopt -s nullglob; echo *.no; GLOBIGNORE="x*:.h*"; echo **/*.rs
+shopt -s failglob; echo *.no; echo NG
+rm -r /tmp/$$-glob')
+[ "$res" == "x.rs
+.h.rs x.rs
+.h.rs a/b/z.rs a/y.rs x.rs
+a/ a/b a/b/z.rs a/y.rs
+a/
+X.TXT
+
+a/b/z.rs a/y.rs" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nullglob; u=http://h/p?q=1; x=none*; echo "$u $x"; [[ -z none* ]] || echo ok')
+[ "$res" == "http://h/p?q=1 none*
+ok" ] || err $LINENO
+
+res=$($com <<< 'shopt -s failglob; x=none*; echo "$x"; [[ none* == none* ]] && echo ok')
+[ "$res" == "none*
+ok" ] || err $LINENO
+
+res=$($com <<< 'shopt -s failglob; for f in none*; do echo NG; done; echo NG
+echo $?')
+[ "$res" == "1" ] || err $LINENO
+
 # split
 
 export RUSTY_BASH_A='a
@@ -1210,6 +1410,24 @@ b
 res=$($com <<< 'echo ${RUSTY_BASH_A}c')
 [ "$res" == "a b c" ] || err $LINENO
 
+res=$($com <<< 'IFS=,; a="x,,y,"; set -- $a; echo $# "$2"; set -- a,b; echo $#')
+[ "$res" == "3 
+1" ] || err $LINENO
+
+res=$($com <<< 'IFS=" ,"; a=" x , y ,z "; printf "<%s>" X${a}Y')
+[ "$res" == "<X><x><y><z><Y>" ] || err $LINENO
+
+res=$($com <<< 'IFS=; a="x y"; set -- $a; echo $#; unset IFS; set -- $a; echo $#')
+[ "$res" == "1
+2" ] || err $LINENO
+
+res=$($com <<< 'IFS=:; p=/a:/b; q=$p; echo "$q"; IFS=,; x=a,b; y=${x}; echo "$y"')
+[ "$res" == "/a:/b
+a,b" ] || err $LINENO
+
+res=$($com <<< 'set a b; x="$@"; y=$@; echo "$x:$y"')
+[ "$res" == "a b:a b" ] || err $LINENO
+
 res=$($com <<< 'mkdir -p tmp; cd tmp; echo .* | grep -F ". .."; cd ..; rmdir tmp')
 [ "$res" == '. ..' ] || err $LINENO
 
@@ -1240,6 +1458,25 @@ res=$($com <<< 'echo {,,}$(date "+%w")')
 res=$($com <<< 'echo $(date) | grep "  "')
 [ "$?" == "1" ] || err $LINENO
 
+# process substitution
+
+res=$($com <<< 'cat <(echo a) <(echo b)')
+[ "$res" == "a
+b" ] || err $LINENO
+
+res=$($com <<< 'diff <(seq 3) <(seq 3) && echo same')
+[ "$res" == "same" ] || err $LINENO
+
+res=$($com <<< 'while read l; do echo "x$l"; done < <(seq 2)')
+[ "$res" == "x1
+x2" ] || err $LINENO
+
+res=$($com <<< '{ echo abc > >(cat); } | cat')
+[ "$res" == "abc" ] || err $LINENO
+
+res=$($com <<< 'echo <(true)')
+[[ "$res" =~ ^/dev/fd/[0-9]+$ ]] || err $LINENO
+
 # array
 
 res=$($com <<< 'A=( a b ); echo ${A[1]}')
@@ -1281,6 +1518,27 @@ res=$($com <<< 'set -e ; false || false ; echo NG')
 res=$($com <<< 'set -e ; while false ; do echo NG ; done ; echo OK')
 [ "$res" == "OK" ] || err $LINENO
 
+res=$($com <<< 'set -e ; { false && true ; } ; ! true ; echo OK ; f () { false && true ; } ; f ; echo NG')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; f () { false ; echo A ; } ; if f ; then f && echo B ; fi ; f ; echo NG')
+[ "$res" == "A
+A
+B" ] || err $LINENO
+
+res=$($com <<< 'set -e ; if ( false ; echo A ) ; then true ; fi ; true && false ; echo NG')
+[ "$res" == "A" ] || err $LINENO
+
+res=$($com <<< 'set -e ; if false ; then : ; fi ; echo OK ; if false ; then : ; elif false ; then : ; fi ; echo $?')
+[ "$res" == "OK
+0" ] || err $LINENO
+
+res=$($com <<< 'trap "echo ERR" ERR ; if false ; then : ; fi ; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; echo $(false ; echo A) ; shopt -s inherit_errexit ; echo $(false ; echo NG) ; x=$(false) ; echo NG')
+[ "$res" == "A" ] || err $LINENO
+
 res=$($com <<< 'set -o pipefail; ls aaaa | false | true')
 [ "$?" == "1" ] || err $LINENO
 